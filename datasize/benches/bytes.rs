@@ -0,0 +1,24 @@
+//! Sizes byte buffers of different lengths. `Vec<u8>` and `String` are sized by their capacity
+//! alone, so the estimate should take the same time regardless of their length.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use datasize::data_size;
+
+fn bytes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bytes");
+    for len in [1 << 10, 1 << 20] {
+        let bytes = vec![0u8; len];
+        let text = "x".repeat(len);
+
+        group.bench_function(format!("Vec<u8>/{}", len), |b| {
+            b.iter(|| data_size(black_box(&bytes)))
+        });
+        group.bench_function(format!("String/{}", len), |b| {
+            b.iter(|| data_size(black_box(&text)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bytes);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+//! Compares sizing nested collections whose innermost elements are non-dynamic, which only
+//! requires visiting the inner collections, to fully dynamic ones, which requires visiting every
+//! element.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use datasize::data_size;
+
+fn nested(c: &mut Criterion) {
+    let shallow: Vec<Vec<u64>> = (0..1000).map(|i| vec![0; i % 100]).collect();
+    let deep: Vec<Vec<Vec<u8>>> = (0..1000)
+        .map(|i| (0..i % 100).map(|_| vec![0; 8]).collect())
+        .collect();
+
+    let mut group = c.benchmark_group("nested");
+    group.bench_function("Vec<Vec<u64>>", |b| {
+        b.iter(|| data_size(black_box(&shallow)))
+    });
+    group.bench_function("Vec<Vec<Vec<u8>>>", |b| {
+        b.iter(|| data_size(black_box(&deep)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, nested);
+criterion_main!(benches);
@@ -0,0 +1,685 @@
+//! Implementations for the types of the `alloc` crate.
+
+extern crate alloc;
+
+#[cfg(feature = "visitor")]
+use super::DataSizeVisitor;
+use super::{
+    allocation_overhead, data_size, is_dynamic, sum_with_limit, DataSize, DynDataSize, Limit,
+};
+#[cfg(feature = "detailed")]
+use super::{detailed_aggregate, detailed_buffer, detailed_storage, MemUsageNode};
+
+use alloc::{borrow::Cow, borrow::ToOwned, boxed::Box, string::String, vec::Vec};
+use core::mem::{size_of, size_of_val};
+
+impl<T> DataSize for Box<T>
+where
+    T: DataSize,
+{
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+
+    const STATIC_HEAP_SIZE: usize = boxed_size::<T>();
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // Total size is the struct itself + its children.
+        box_allocation_size::<T>(self) + data_size::<T>(self)
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        // The boxed value is nested one level deeper, unless it can be sized without visiting it.
+        let sz_value = if is_dynamic::<T>() {
+            limit.descend(|limit| (**self).estimate_heap_size_with_limit(limit))
+        } else {
+            T::STATIC_HEAP_SIZE
+        };
+
+        box_allocation_size::<T>(self) + sz_value
+    }
+
+    #[cfg(feature = "visitor")]
+    #[inline]
+    fn visit<V: DataSizeVisitor>(&self, visitor: &mut V) {
+        // Boxing a zero-sized value does not allocate.
+        if size_of::<T>() == 0 {
+            return (**self).visit(visitor);
+        }
+
+        visitor.enter(box_allocation_size::<T>(self), 1);
+        (**self).visit(visitor);
+        visitor.leave();
+    }
+
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        detailed_storage(
+            "allocation",
+            box_allocation_size::<T>(self),
+            vec![("value", (**self).estimate_detailed_heap_size())],
+        )
+    }
+}
+
+/// Returns the size of the allocation backing a `Box<T>`.
+#[inline]
+const fn boxed_size<T>() -> usize {
+    size_of::<T>() + allocation_overhead(size_of::<T>())
+}
+
+/// Returns the size of the allocation of the `Box` holding `value`.
+///
+/// This is `boxed_size::<T>()`, unless queried by `data_size_usable`.
+#[inline]
+fn box_allocation_size<T>(value: &T) -> usize {
+    // SAFETY: The value is boxed, so it lives at the start of an allocation of the global
+    //         allocator, unless it is zero-sized.
+    let size = unsafe { allocated_size((value as *const T).cast(), size_of::<T>()) };
+    size + allocation_overhead(size_of::<T>())
+}
+
+/// Returns the size of the allocation at `ptr` made by the global allocator for `requested` bytes.
+///
+/// With the `usable-size` feature enabled, this is the usable size reported by the allocator
+/// during calls to `data_size_usable`.
+///
+/// # Safety
+///
+/// Unless `requested` is zero, `ptr` must point to the start of a live allocation of the global
+/// allocator.
+#[cfg(feature = "usable-size")]
+#[inline]
+unsafe fn allocated_size(ptr: *const u8, requested: usize) -> usize {
+    crate::usable_size::usable_size(ptr, requested)
+}
+
+#[cfg(not(feature = "usable-size"))]
+#[inline]
+unsafe fn allocated_size(_ptr: *const u8, requested: usize) -> usize {
+    requested
+}
+
+impl DataSize for Box<str> {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // A boxed `str` has no spare capacity, its allocation is exactly its length.
+        // SAFETY: A non-empty boxed `str` is an allocation of the global allocator.
+        let size = unsafe { allocated_size(self.as_ptr(), self.len()) };
+        size + allocation_overhead(self.len())
+    }
+}
+
+// Unsized values cannot share the impl for `Box<T>`, whose `STATIC_HEAP_SIZE` depends on the size
+// of `T`, so boxed slices are implemented separately. The allocation is sized by `size_of_val`.
+impl<T> DataSize for Box<[T]>
+where
+    T: DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        boxed_slice_size(self) + (**self).estimate_heap_size()
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        // The elements are nested one level deeper, like the value of a `Box<T>`.
+        let sz_elements = if is_dynamic::<[T]>() {
+            limit.descend(|limit| (**self).estimate_heap_size_with_limit(limit))
+        } else {
+            0
+        };
+
+        boxed_slice_size(self) + sz_elements
+    }
+
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        detailed_storage(
+            "allocation",
+            boxed_slice_size(self),
+            vec![("elements", detailed_aggregate(self.iter()))],
+        )
+    }
+}
+
+/// Returns the size of the allocation of a boxed slice.
+#[inline]
+fn boxed_slice_size<T>(slice: &[T]) -> usize {
+    let size = size_of_val(slice);
+    // SAFETY: A non-empty boxed slice is an allocation of the global allocator.
+    let allocated = unsafe { allocated_size(slice.as_ptr().cast(), size) };
+    allocated + allocation_overhead(size)
+}
+
+// A boxed trait object can only be sized by the stack size of its concrete value, any heap data
+// owned by the value itself is unknown. The estimate is thus a lower bound.
+macro_rules! boxed_any_heap_size {
+    ($($ty:ty),*) => {
+        $(impl DataSize for Box<$ty> {
+            const IS_DYNAMIC: bool = true;
+
+            const STATIC_HEAP_SIZE: usize = 0;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                let size = size_of_val::<$ty>(&**self);
+                size + allocation_overhead(size)
+            }
+        })*
+    };
+}
+
+boxed_any_heap_size!(
+    dyn core::any::Any,
+    dyn core::any::Any + Send,
+    dyn core::any::Any + Send + Sync
+);
+
+// Unlike other trait objects, a boxed `DynDataSize` can report the heap data of its value as well.
+macro_rules! boxed_dyn_data_size_heap_size {
+    ($($ty:ty),*) => {
+        $(impl DataSize for Box<$ty> {
+            const IS_DYNAMIC: bool = true;
+
+            const STATIC_HEAP_SIZE: usize = 0;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                boxed_dyn_size::<$ty>(&**self) + (**self).dyn_estimate_heap_size()
+            }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+                boxed_dyn_size::<$ty>(&**self)
+                    + limit.descend(|limit| (**self).dyn_estimate_heap_size_with_limit(limit))
+            }
+        })*
+    };
+}
+
+boxed_dyn_data_size_heap_size!(
+    dyn DynDataSize,
+    dyn DynDataSize + Send,
+    dyn DynDataSize + Send + Sync
+);
+
+/// Returns the size of the allocation of a boxed trait object.
+#[inline]
+fn boxed_dyn_size<T: ?Sized>(value: &T) -> usize {
+    let size = size_of_val(value);
+    // SAFETY: A boxed value that is not zero-sized is an allocation of the global allocator.
+    let allocated = unsafe { allocated_size((value as *const T).cast(), size) };
+    allocated + allocation_overhead(size)
+}
+
+// Covers borrowed slices and strings as well, e.g. the owned variant of a `Cow<[T]>` is sized like
+// the `Vec<T>` it holds.
+impl<'a, T> DataSize for Cow<'a, T>
+where
+    T: 'a + ToOwned + ?Sized,
+    <T as ToOwned>::Owned: DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        match self {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(inner) => inner.estimate_heap_size(),
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        match self {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(inner) => inner.estimate_heap_size_with_limit(limit),
+        }
+    }
+}
+
+// Please see the notes in the module docs on why Arcs are not counted. With the
+// `count-unique-shared` feature enabled, the pointee of an `Arc` or `Rc` without any other strong
+// or weak references is counted (e.g. the bytes of an `Arc<str>`), but not any heap data owned by
+// the pointee itself. The `strict-shared` feature removes both impls, so every shared pointer has
+// to be handled explicitly.
+#[cfg(not(feature = "strict-shared"))]
+impl<T: ?Sized> DataSize for alloc::sync::Arc<T> {
+    const IS_DYNAMIC: bool = cfg!(feature = "count-unique-shared");
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        if cfg!(feature = "count-unique-shared")
+            && alloc::sync::Arc::strong_count(self) == 1
+            && alloc::sync::Arc::weak_count(self) == 0
+        {
+            size_of_val::<T>(self)
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(not(feature = "strict-shared"))]
+impl<T: ?Sized> DataSize for alloc::rc::Rc<T> {
+    const IS_DYNAMIC: bool = cfg!(feature = "count-unique-shared");
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        if cfg!(feature = "count-unique-shared")
+            && alloc::rc::Rc::strong_count(self) == 1
+            && alloc::rc::Rc::weak_count(self) == 0
+        {
+            size_of_val::<T>(self)
+        } else {
+            0
+        }
+    }
+}
+
+/// Estimates the heap size of an `Arc`, counting its pointee regardless of how often it is shared.
+///
+/// Includes the pointee itself, its heap data and the reference counts stored alongside it. Meant
+/// to be used through `#[data_size(with = count_arc)]` on the one field considered the owner of
+/// the `Arc`. Using it on multiple fields pointing at the same allocation counts it multiple times.
+#[inline]
+pub fn count_arc<T: DataSize>(arc: &alloc::sync::Arc<T>) -> usize {
+    shared_allocation_size::<T>() + data_size::<T>(arc)
+}
+
+/// Estimates the heap size of an `Rc`, counting its pointee regardless of how often it is shared.
+///
+/// See `count_arc` for details.
+#[inline]
+pub fn count_rc<T: DataSize>(rc: &alloc::rc::Rc<T>) -> usize {
+    shared_allocation_size::<T>() + data_size::<T>(rc)
+}
+
+/// Returns the size of the allocation backing an `Arc<T>` or `Rc<T>`.
+///
+/// Both store a strong and a weak count of `usize` each in front of the value.
+#[inline]
+pub(crate) fn shared_allocation_size<T>() -> usize {
+    let size = 2 * size_of::<usize>() + size_of::<T>();
+    size + allocation_overhead(size)
+}
+
+// CONTAINERS
+
+/// Returns the number of elements the buffer of a `Vec`-like collection is sized by.
+///
+/// This is the `capacity`, as spare capacity is allocated heap memory as well, unless the
+/// `vec-len-sizing` feature is enabled, in which case `len` is used instead.
+#[inline]
+pub(crate) fn buffer_len(len: usize, capacity: usize) -> usize {
+    if cfg!(feature = "vec-len-sizing") {
+        len
+    } else {
+        capacity
+    }
+}
+
+/// Returns the size of the buffer of a `Vec`-like collection of `T`, including allocator overhead.
+#[inline]
+fn buffer_size<T>(len: usize, capacity: usize) -> usize {
+    // The buffer is allocated according to its capacity, regardless of how it is sized.
+    buffer_len(len, capacity) * size_of::<T>() + allocation_overhead(capacity * size_of::<T>())
+}
+
+/// Returns the size of the buffer of a `Vec` allocated by the global allocator.
+#[cfg(not(feature = "allocator_api"))]
+#[inline]
+fn global_buffer_size<T>(vec: &Vec<T>) -> usize {
+    // SAFETY: A vector with a non-empty buffer owns an allocation of the global allocator.
+    unsafe { raw_buffer_size::<T>(vec.as_ptr(), vec.len(), vec.capacity()) }
+}
+
+/// Returns the size of a `Vec`-like buffer of `len` out of `capacity` elements at `ptr`.
+///
+/// Unless sized by `len`, this is the size of its allocation as returned by `allocated_size`. Only
+/// the length and capacity are used, the elements are never accessed.
+///
+/// # Safety
+///
+/// Unless the buffer is empty, `ptr` must point to the start of a live allocation of the global
+/// allocator for `capacity` elements.
+#[inline]
+unsafe fn raw_buffer_size<T>(ptr: *const T, len: usize, capacity: usize) -> usize {
+    if cfg!(feature = "vec-len-sizing") {
+        return buffer_size::<T>(len, capacity);
+    }
+
+    let requested = capacity * size_of::<T>();
+    let size = allocated_size(ptr.cast(), requested);
+    size + allocation_overhead(requested)
+}
+
+/// Returns the size of the buffer of a `Vec` using a custom allocator.
+///
+/// The allocator is unknown, so the requested size is always used.
+#[cfg(feature = "allocator_api")]
+#[inline]
+fn allocator_buffer_size<T, A: alloc::alloc::Allocator>(vec: &Vec<T, A>) -> usize {
+    buffer_size::<T>(vec.len(), vec.capacity())
+}
+
+/// Returns the estimated size of the nodes of a B-tree holding `len` entries of `K` and `V`.
+///
+/// The B-tree of the standard library stores up to 11 entries per node, and every node except the
+/// root is at least half full. Assuming about 8 entries per node on average, a tree of `len`
+/// entries consists of `ceil(len / 8)` nodes. Every node is a separate allocation with room for 11
+/// keys and values, a parent pointer and two `u16` indices. The internal nodes additionally hold 12
+/// child pointers; with about 8 children each, a tree of `n` nodes has approximately `(n - 1) / 7`
+/// internal ones.
+#[inline]
+pub(crate) fn btree_size<K, V>(len: usize) -> usize {
+    const CAPACITY: usize = 11;
+
+    let nodes = len.div_ceil(8);
+    let internal_nodes = nodes.saturating_sub(1) / 7;
+
+    let leaf_size =
+        size_of::<usize>() + 2 * size_of::<u16>() + CAPACITY * (size_of::<K>() + size_of::<V>());
+    let edges_size = (CAPACITY + 1) * size_of::<usize>();
+
+    nodes * (leaf_size + allocation_overhead(leaf_size)) + internal_nodes * edges_size
+}
+
+// Note: The flat allocation of a `Vec` is based on its `capacity`, not its `len` (see
+// `buffer_len`). Only the heap data of initialized elements is added on top.
+//
+// With the `allocator_api` feature, vectors using any allocator are supported. The allocator itself
+// is not counted, and the usable size of their buffers is never queried.
+macro_rules! vec_heap_size {
+    ($buffer_size:ident $(, $alloc:ident: $bound:path)?) => {
+        impl<T $(, $alloc: $bound)?> DataSize for Vec<T $(, $alloc)?>
+        where
+            T: DataSize,
+        {
+            const IS_DYNAMIC: bool = true;
+
+            const STATIC_HEAP_SIZE: usize = 0;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                // We do not include the `STATIC_HEAP_SIZE`, since the heap data has not been
+                // allocated yet.
+                let sz_base = $buffer_size(self);
+
+                // Only dynamic elements are visited. Each of them applies the same shortcut, e.g.
+                // the inner vectors of a `Vec<Vec<u64>>` are sized without visiting their elements.
+                let sz_used = if is_dynamic::<T>() {
+                    self.iter().map(DataSize::estimate_heap_size).sum()
+                } else {
+                    self.len() * T::STATIC_HEAP_SIZE
+                };
+
+                sz_base + sz_used
+            }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+                let sz_base = $buffer_size(self);
+
+                let sz_used = if is_dynamic::<T>() {
+                    sum_with_limit(self, limit)
+                } else {
+                    self.len() * T::STATIC_HEAP_SIZE
+                };
+
+                sz_base + sz_used
+            }
+
+            #[cfg(feature = "visitor")]
+            #[inline]
+            fn visit<V: DataSizeVisitor>(&self, visitor: &mut V) {
+                // Elements without any heap data have nothing to report, so they are not visited.
+                let visit_elements = T::IS_DYNAMIC || T::STATIC_HEAP_SIZE > 0;
+
+                // An empty `Vec` or one of zero-sized elements has no buffer allocated. With
+                // `vec-len-sizing`, an empty `Vec` with spare capacity reports no buffer either.
+                let sz_base = $buffer_size(self);
+                if sz_base == 0 {
+                    if visit_elements {
+                        self.iter().for_each(|item| item.visit(visitor));
+                    }
+                    return;
+                }
+
+                visitor.enter(sz_base, self.len());
+                if visit_elements {
+                    self.iter().for_each(|item| item.visit(visitor));
+                }
+                visitor.leave();
+            }
+
+            #[cfg(feature = "detailed")]
+            #[inline]
+            fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+                // All elements are aggregated into a single node, keeping the tree small for large vectors.
+                detailed_buffer(
+                    "buffer",
+                    $buffer_size(self),
+                    self.len() * size_of::<T>(),
+                    vec![("elements", detailed_aggregate(self))],
+                )
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "allocator_api"))]
+vec_heap_size!(global_buffer_size);
+#[cfg(feature = "allocator_api")]
+vec_heap_size!(allocator_buffer_size, A: alloc::alloc::Allocator);
+
+impl<T> DataSize for alloc::collections::VecDeque<T>
+where
+    T: DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // We can treat a `VecDeque` exactly the same as a `Vec`.
+        let sz_base = buffer_size::<T>(self.len(), self.capacity());
+
+        let sz_used = if is_dynamic::<T>() {
+            self.iter().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        };
+
+        sz_base + sz_used
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        let sz_base = buffer_size::<T>(self.len(), self.capacity());
+
+        let sz_used = if is_dynamic::<T>() {
+            sum_with_limit(self, limit)
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        };
+
+        sz_base + sz_used
+    }
+
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        detailed_buffer(
+            "buffer",
+            buffer_size::<T>(self.len(), self.capacity()),
+            self.len() * size_of::<T>(),
+            vec![("elements", detailed_aggregate(self))],
+        )
+    }
+}
+
+impl<T> DataSize for alloc::collections::BinaryHeap<T>
+where
+    T: DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // A `BinaryHeap` is backed by a `Vec`, in no particular order, and sized like it.
+        let sz_base = buffer_size::<T>(self.len(), self.capacity());
+
+        let sz_used = if is_dynamic::<T>() {
+            self.iter().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        };
+
+        sz_base + sz_used
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        let sz_base = buffer_size::<T>(self.len(), self.capacity());
+
+        let sz_used = if is_dynamic::<T>() {
+            sum_with_limit(self, limit)
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        };
+
+        sz_base + sz_used
+    }
+}
+
+impl DataSize for String {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // A `String` is a `Vec<u8>`, sized without looking at its contents.
+        // SAFETY: A string with a non-empty buffer owns an allocation of the global allocator.
+        unsafe { raw_buffer_size::<u8>(self.as_ptr(), self.len(), self.capacity()) }
+    }
+
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        detailed_buffer("buffer", self.estimate_heap_size(), self.len(), vec![])
+    }
+}
+
+impl<K, V> DataSize for alloc::collections::BTreeMap<K, V>
+where
+    K: DataSize,
+    V: DataSize,
+{
+    // The nodes holding the entries are estimated by `btree_size`, see there for the heuristic.
+
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let mut size = btree_size::<K, V>(self.len());
+
+        if is_dynamic::<K>() || is_dynamic::<V>() {
+            for (key, value) in self.iter() {
+                size += key.estimate_heap_size() + value.estimate_heap_size();
+            }
+        } else {
+            size += self.len() * (K::STATIC_HEAP_SIZE + V::STATIC_HEAP_SIZE);
+        }
+        size
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if !(is_dynamic::<K>() || is_dynamic::<V>()) {
+            return self.estimate_heap_size();
+        }
+
+        if self.is_empty() {
+            return 0;
+        }
+
+        btree_size::<K, V>(self.len())
+            + limit.descend(|limit| {
+                let mut size = 0;
+                for (key, value) in self.iter() {
+                    if !limit.consume_node() {
+                        break;
+                    }
+                    size += key.estimate_heap_size_with_limit(limit)
+                        + value.estimate_heap_size_with_limit(limit);
+                }
+                size
+            })
+    }
+
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        detailed_storage(
+            "nodes",
+            btree_size::<K, V>(self.len()),
+            vec![
+                ("keys", detailed_aggregate(self.keys())),
+                ("values", detailed_aggregate(self.values())),
+            ],
+        )
+    }
+}
+
+impl<T> DataSize for alloc::collections::BTreeSet<T>
+where
+    T: DataSize,
+{
+    // A BTreeSet<T> is implemented as BTreeMap<T, ()> in the standard library, so we use the same
+    // estimate as above.
+
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let size = btree_size::<T, ()>(self.len());
+
+        if is_dynamic::<T>() {
+            size + self.iter().map(T::estimate_heap_size).sum::<usize>()
+        } else {
+            size + self.len() * T::STATIC_HEAP_SIZE
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if is_dynamic::<T>() {
+            btree_size::<T, ()>(self.len()) + sum_with_limit(self, limit)
+        } else {
+            self.estimate_heap_size()
+        }
+    }
+}
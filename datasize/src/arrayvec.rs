@@ -0,0 +1,75 @@
+use super::{is_dynamic, sum_with_limit, DataSize, Limit};
+
+// Both types store their contents inline, so only the heap data of the elements themselves is
+// counted.
+impl<T, const CAP: usize> DataSize for arrayvec::ArrayVec<T, CAP>
+where
+    T: DataSize,
+{
+    // The number of elements varies, so any element with heap data makes the whole vec dynamic.
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC || T::STATIC_HEAP_SIZE > 0;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        if is_dynamic::<T>() {
+            self.iter().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if is_dynamic::<T>() {
+            sum_with_limit(self.iter(), limit)
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        }
+    }
+}
+
+impl<const CAP: usize> DataSize for arrayvec::ArrayString<CAP> {
+    const IS_DYNAMIC: bool = false;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_array_vec() {
+        let mut plain: arrayvec::ArrayVec<u8, 4> = arrayvec::ArrayVec::new();
+        plain.push(1);
+        plain.push(2);
+
+        assert!(!arrayvec::ArrayVec::<u8, 4>::IS_DYNAMIC);
+        assert_eq!(data_size(&plain), 0);
+
+        let mut nested: arrayvec::ArrayVec<Vec<u8>, 4> = arrayvec::ArrayVec::new();
+        nested.push(vec![0; 16]);
+        nested.push(vec![0; 8]);
+
+        assert!(arrayvec::ArrayVec::<Vec<u8>, 4>::IS_DYNAMIC);
+        assert_eq!(
+            data_size(&nested),
+            16 + allocation_overhead(16) + 8 + allocation_overhead(8)
+        );
+    }
+
+    #[test]
+    fn test_array_string() {
+        let value: arrayvec::ArrayString<16> = arrayvec::ArrayString::from("hello").unwrap();
+
+        assert!(!arrayvec::ArrayString::<16>::IS_DYNAMIC);
+        assert_eq!(data_size(&value), 0);
+    }
+}
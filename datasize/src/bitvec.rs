@@ -0,0 +1,45 @@
+use bitvec::{order::BitOrder, store::BitStore, vec::BitVec};
+
+use super::{alloc::buffer_len, allocation_overhead, DataSize};
+
+// A `BitVec` packs its bits into a buffer of `T`, so it is sized by its capacity in bits instead of
+// counting one element per bit.
+impl<T, O> DataSize for BitVec<T, O>
+where
+    T: BitStore,
+    O: BitOrder,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let bytes = buffer_len(self.len(), self.capacity()).div_ceil(8);
+        bytes + allocation_overhead(self.capacity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::prelude::*;
+
+    use crate::{allocation_overhead, data_size};
+
+    #[test]
+    fn test_bitvec() {
+        let bits: BitVec<u8, Lsb0> = bitvec![u8, Lsb0; 1; 1000];
+        assert_eq!(data_size(&bits), 125 + allocation_overhead(125));
+        // Unlike a `Vec<bool>`, which uses a byte per element.
+        assert_eq!(
+            data_size(&vec![true; 1000]),
+            1000 + allocation_overhead(1000)
+        );
+
+        let bits: BitVec = BitVec::repeat(false, 1000);
+        let size = data_size(&bits) - allocation_overhead(125);
+        assert!((125..=125 + 8).contains(&size), "{}", size);
+
+        assert_eq!(data_size(&BitVec::<usize, Lsb0>::new()), 0);
+    }
+}
@@ -0,0 +1,56 @@
+use super::{non_dynamic_const_heap_size, DataSize};
+
+non_dynamic_const_heap_size!(
+    chrono::NaiveDate
+    chrono::NaiveTime
+    chrono::NaiveDateTime
+    chrono::Duration
+    chrono::Utc
+    chrono::FixedOffset,
+    0
+);
+
+// The offset stored alongside the time is assumed to hold no heap data, which is the case for all
+// time zones provided by `chrono` itself.
+impl<Tz> DataSize for chrono::DateTime<Tz>
+where
+    Tz: chrono::TimeZone,
+{
+    const IS_DYNAMIC: bool = false;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    #[test]
+    fn test_date_times() {
+        #[derive(DataSize)]
+        struct Schedule {
+            events: Vec<DateTime<Utc>>,
+            start: chrono::NaiveDate,
+            timeout: chrono::Duration,
+        }
+
+        let schedule = Schedule {
+            events: vec![
+                Utc.timestamp_opt(0, 0).unwrap(),
+                Utc.timestamp_opt(1_600_000_000, 0).unwrap(),
+            ],
+            start: chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            timeout: chrono::Duration::seconds(30),
+        };
+
+        let events = 2 * core::mem::size_of::<DateTime<Utc>>();
+        assert!(!DateTime::<Utc>::IS_DYNAMIC);
+        assert_eq!(data_size(&schedule), events + allocation_overhead(events));
+    }
+}
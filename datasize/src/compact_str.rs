@@ -0,0 +1,54 @@
+use super::{allocation_overhead, DataSize};
+
+use compact_str::CompactString;
+
+// A `CompactString` stores strings of up to 24 bytes (on 64-bit platforms) inline, only longer ones
+// are moved to a heap buffer of `capacity` bytes. Strings built from a `&'static str` reference it
+// without allocating.
+impl DataSize for CompactString {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        if self.is_heap_allocated() {
+            self.capacity() + allocation_overhead(self.capacity())
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compact_str::CompactString;
+
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_compact_string() {
+        #[derive(DataSize)]
+        struct Label {
+            text: CompactString,
+        }
+
+        let short = CompactString::new("inline");
+        assert!(!short.is_heap_allocated());
+        assert_eq!(data_size(&short), 0);
+
+        let long = CompactString::new("a string that is too long to be stored inline");
+        assert!(long.is_heap_allocated());
+        assert_eq!(
+            data_size(&long),
+            long.capacity() + allocation_overhead(long.capacity())
+        );
+        assert!(data_size(&long) >= long.len());
+
+        let label = Label {
+            text: CompactString::with_capacity(128),
+        };
+        assert_eq!(data_size(&label), 128 + allocation_overhead(128));
+    }
+}
@@ -0,0 +1,52 @@
+use super::{non_dynamic_const_heap_size, DataSize};
+
+// Vectors.
+non_dynamic_const_heap_size!(
+    glam::Vec2 glam::Vec3 glam::Vec3A glam::Vec4
+    glam::DVec2 glam::DVec3 glam::DVec4
+    glam::IVec2 glam::IVec3 glam::IVec4
+    glam::UVec2 glam::UVec3 glam::UVec4
+    glam::BVec2 glam::BVec3 glam::BVec4,
+    0
+);
+
+// Matrices, quaternions and affine transforms.
+non_dynamic_const_heap_size!(
+    glam::Mat2 glam::Mat3 glam::Mat3A glam::Mat4
+    glam::DMat2 glam::DMat3 glam::DMat4
+    glam::Quat glam::DQuat
+    glam::Affine2 glam::Affine3A glam::DAffine2 glam::DAffine3,
+    0
+);
+
+#[cfg(test)]
+mod tests {
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_glam_types() {
+        #[derive(DataSize)]
+        struct Transform {
+            translation: glam::Vec3,
+            rotation: glam::Quat,
+            scale: glam::Vec3,
+            matrix: glam::Mat4,
+        }
+
+        let transform = Transform {
+            translation: glam::Vec3::new(1.0, 2.0, 3.0),
+            rotation: glam::Quat::IDENTITY,
+            scale: glam::Vec3::ONE,
+            matrix: glam::Mat4::IDENTITY,
+        };
+
+        assert!(!Transform::IS_DYNAMIC);
+        assert_eq!(Transform::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&transform), 0);
+
+        let transforms = vec![transform];
+        let size = core::mem::size_of::<Transform>();
+        assert_eq!(data_size(&transforms), size + allocation_overhead(size));
+    }
+}
@@ -0,0 +1,223 @@
+//! Sizing of graphs made of shared pointers.
+
+extern crate alloc;
+
+use alloc::{collections::BTreeSet, rc::Rc, sync::Arc, vec::Vec};
+use core::cell::RefCell;
+
+use super::alloc::shared_allocation_size;
+
+/// A node of a graph whose edges are `Rc`s or `Arc`s, e.g. an `Rc<RefCell<Node>>`.
+///
+/// Implemented by the user to expose the children of a node to a `GraphSizer`.
+pub trait GraphNode {
+    /// Returns the heap size owned by the node, excluding any nodes reachable through shared
+    /// pointers.
+    ///
+    /// If the node derives `DataSize`, this is usually just `data_size(self)`, as shared pointers
+    /// are not counted by it. With the `count-unique-shared` feature enabled, `data_size` does
+    /// count an edge held by no other strong or weak reference, which the `GraphSizer` counts again
+    /// when visiting it. Edges should be skipped through `#[data_size(skip)]` in that case.
+    fn own_heap_size(&self) -> usize;
+
+    /// Adds all children of the node to `sizer`, using `GraphSizer::add_rc` and
+    /// `GraphSizer::add_arc`.
+    fn add_children(&self, sizer: &mut GraphSizer);
+}
+
+// A node that is currently borrowed mutably cannot be inspected and is counted without any heap
+// data or children, instead of panicking.
+impl<T: GraphNode> GraphNode for RefCell<T> {
+    #[inline]
+    fn own_heap_size(&self) -> usize {
+        self.try_borrow().map_or(0, |node| node.own_heap_size())
+    }
+
+    #[inline]
+    fn add_children(&self, sizer: &mut GraphSizer) {
+        if let Ok(node) = self.try_borrow() {
+            node.add_children(sizer);
+        }
+    }
+}
+
+/// A node that was reached but not inspected yet.
+enum Pending {
+    Rc(Rc<dyn GraphNode>),
+    Arc(Arc<dyn GraphNode>),
+}
+
+/// Estimates the heap size of a graph of `Rc`s or `Arc`s, counting every node exactly once.
+///
+/// Nodes are identified by the address of their allocation, so cycles and nodes reachable through
+/// multiple paths are handled correctly. The graph is traversed iteratively, arbitrarily deep
+/// graphs do not overflow the stack.
+///
+/// ```rust
+/// use std::{cell::RefCell, rc::Rc};
+/// use datasize::{GraphNode, GraphSizer};
+///
+/// struct Node {
+///     next: Option<Rc<RefCell<Node>>>,
+/// }
+///
+/// impl GraphNode for Node {
+///     fn own_heap_size(&self) -> usize {
+///         0
+///     }
+///
+///     fn add_children(&self, sizer: &mut GraphSizer) {
+///         if let Some(ref next) = self.next {
+///             sizer.add_rc(next);
+///         }
+///     }
+/// }
+///
+/// let node = Rc::new(RefCell::new(Node { next: None }));
+/// node.borrow_mut().next = Some(node.clone());
+///
+/// let mut sizer = GraphSizer::new();
+/// sizer.add_rc(&node);
+/// assert!(sizer.total() > 0);
+/// # node.borrow_mut().next = None;
+/// ```
+#[derive(Default)]
+pub struct GraphSizer {
+    /// The addresses of all nodes reached so far.
+    visited: BTreeSet<usize>,
+    /// Nodes that were reached, but whose children have not been added yet.
+    pending: Vec<Pending>,
+    /// The size of all nodes reached so far.
+    size: usize,
+}
+
+impl GraphSizer {
+    /// Creates a new sizer without any nodes.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the node behind an `Rc`, unless it was already added.
+    pub fn add_rc<T: GraphNode + 'static>(&mut self, node: &Rc<T>) {
+        if self.visited.insert(Rc::as_ptr(node) as *const () as usize) {
+            self.size += shared_allocation_size::<T>();
+            self.pending.push(Pending::Rc(node.clone()));
+        }
+    }
+
+    /// Adds the node behind an `Arc`, unless it was already added.
+    pub fn add_arc<T: GraphNode + 'static>(&mut self, node: &Arc<T>) {
+        if self.visited.insert(Arc::as_ptr(node) as *const () as usize) {
+            self.size += shared_allocation_size::<T>();
+            self.pending.push(Pending::Arc(node.clone()));
+        }
+    }
+
+    /// Traverses all nodes reachable from the added ones and returns their total heap size.
+    ///
+    /// Includes the allocations of the nodes themselves.
+    pub fn total(mut self) -> usize {
+        while let Some(pending) = self.pending.pop() {
+            let node: &dyn GraphNode = match pending {
+                Pending::Rc(ref rc) => &**rc,
+                Pending::Arc(ref arc) => &**arc,
+            };
+
+            self.size += node.own_heap_size();
+            node.add_children(&mut self);
+        }
+
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::RefCell;
+    use core::mem::size_of;
+    use std::{rc::Rc, sync::Arc};
+
+    use super::{GraphNode, GraphSizer};
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[derive(DataSize)]
+    struct Node {
+        payload: Vec<u8>,
+        #[data_size(skip)]
+        edges: Vec<Rc<RefCell<Node>>>,
+    }
+
+    impl GraphNode for Node {
+        fn own_heap_size(&self) -> usize {
+            data_size(self)
+        }
+
+        fn add_children(&self, sizer: &mut GraphSizer) {
+            for edge in &self.edges {
+                sizer.add_rc(edge);
+            }
+        }
+    }
+
+    fn node(payload: usize) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node {
+            payload: vec![0; payload],
+            edges: Vec::new(),
+        }))
+    }
+
+    /// The size of the allocation of a node, including the reference counts.
+    const NODE_ALLOCATION: usize = 2 * size_of::<usize>() + size_of::<RefCell<Node>>();
+
+    /// The total size of a node with a payload of `payload` bytes.
+    const fn node_size(payload: usize) -> usize {
+        NODE_ALLOCATION
+            + allocation_overhead(NODE_ALLOCATION)
+            + payload
+            + allocation_overhead(payload)
+    }
+
+    #[test]
+    fn test_graph_cycle() {
+        let a = node(10);
+        let b = node(20);
+        a.borrow_mut().edges.push(b.clone());
+        b.borrow_mut().edges.push(a.clone());
+
+        let mut sizer = GraphSizer::new();
+        sizer.add_rc(&a);
+        let total = sizer.total();
+
+        // Both nodes are counted exactly once, starting from either of them.
+        assert_eq!(total, node_size(10) + node_size(20));
+
+        let mut sizer = GraphSizer::new();
+        sizer.add_rc(&b);
+        sizer.add_rc(&a);
+        assert_eq!(sizer.total(), total);
+
+        // Break the cycle to not leak the nodes.
+        a.borrow_mut().edges.clear();
+    }
+
+    #[test]
+    fn test_graph_arc() {
+        struct Leaf(Vec<u64>);
+
+        impl GraphNode for Leaf {
+            fn own_heap_size(&self) -> usize {
+                data_size(&self.0)
+            }
+
+            fn add_children(&self, _sizer: &mut GraphSizer) {}
+        }
+
+        let leaf = Arc::new(Leaf(vec![1, 2, 3]));
+        let mut sizer = GraphSizer::new();
+        sizer.add_arc(&leaf);
+        sizer.add_arc(&leaf.clone());
+        assert_eq!(sizer.total(), crate::count_arc(&Arc::new(vec![1u64, 2, 3])));
+    }
+}
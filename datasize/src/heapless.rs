@@ -0,0 +1,100 @@
+use super::{is_dynamic, sum_with_limit, DataSize, Limit};
+
+// All `heapless` collections store their contents inline with a fixed capacity, so only the heap
+// data of the elements themselves is counted.
+
+/// Implements `DataSize` for a `heapless` sequence of elements of type `T`.
+macro_rules! heapless_sequence_heap_size {
+    ($ty:ty $(, $param:ident)*) => {
+        impl<T $(, $param)*, const N: usize> DataSize for $ty
+        where
+            T: DataSize,
+        {
+            // The number of elements varies, so any element with heap data makes it dynamic.
+            const IS_DYNAMIC: bool = T::IS_DYNAMIC || T::STATIC_HEAP_SIZE > 0;
+
+            const STATIC_HEAP_SIZE: usize = 0;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                if is_dynamic::<T>() {
+                    self.iter().map(DataSize::estimate_heap_size).sum()
+                } else {
+                    self.len() * T::STATIC_HEAP_SIZE
+                }
+            }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+                if is_dynamic::<T>() {
+                    sum_with_limit(self.iter(), limit)
+                } else {
+                    self.len() * T::STATIC_HEAP_SIZE
+                }
+            }
+        }
+    };
+}
+
+heapless_sequence_heap_size!(heapless::Vec<T, N>);
+heapless_sequence_heap_size!(heapless::Deque<T, N>);
+heapless_sequence_heap_size!(heapless::IndexSet<T, S, N>, S);
+
+// The hasher of an `IndexMap` is a zero-sized `BuildHasherDefault` in practice, so it is not counted.
+impl<K, V, S, const N: usize> DataSize for heapless::IndexMap<K, V, S, N>
+where
+    K: DataSize,
+    V: DataSize,
+{
+    const IS_DYNAMIC: bool =
+        K::IS_DYNAMIC || K::STATIC_HEAP_SIZE > 0 || V::IS_DYNAMIC || V::STATIC_HEAP_SIZE > 0;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let sz_keys = if is_dynamic::<K>() {
+            self.keys().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * K::STATIC_HEAP_SIZE
+        };
+
+        let sz_values = if is_dynamic::<V>() {
+            self.values().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * V::STATIC_HEAP_SIZE
+        };
+
+        sz_keys + sz_values
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if !(is_dynamic::<K>() || is_dynamic::<V>()) || self.is_empty() {
+            return self.estimate_heap_size();
+        }
+
+        limit.descend(|limit| {
+            let mut size = 0;
+            for (key, value) in self.iter() {
+                if !limit.consume_node() {
+                    break;
+                }
+                size += key.estimate_heap_size_with_limit(limit)
+                    + value.estimate_heap_size_with_limit(limit);
+            }
+            size
+        })
+    }
+}
+
+impl<const N: usize> DataSize for heapless::String<N> {
+    const IS_DYNAMIC: bool = false;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
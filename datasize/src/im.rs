@@ -0,0 +1,216 @@
+use super::{is_dynamic, sum_with_limit, DataSize, Limit};
+use core::mem::size_of;
+use std::hash::{BuildHasher, Hash};
+
+use im::{HashMap, HashSet, OrdMap, OrdSet, Vector};
+
+// The collections of `im` are trees of reference counted chunks, which are shared between clones
+// and only copied when modified. Their layout is not exposed, so every collection is estimated by
+// the size of its elements, ignoring partially filled chunks and the nodes of the tree. Chunks
+// shared with other collections are counted for each of them, so the total of several clones of a
+// collection overcounts, while a single collection is underestimated slightly.
+
+impl<A> DataSize for Vector<A>
+where
+    A: Clone + DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        vector_storage_size(self) + elements_heap_size(self.len(), self.iter())
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        vector_storage_size(self) + elements_heap_size_with_limit(self.len(), self.iter(), limit)
+    }
+}
+
+impl<K, V, S> DataSize for HashMap<K, V, S>
+where
+    K: Clone + Hash + Eq + DataSize,
+    V: Clone + DataSize,
+    S: BuildHasher,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.len() * size_of::<(K, V)>()
+            + elements_heap_size(self.len(), self.keys())
+            + elements_heap_size(self.len(), self.values())
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.len() * size_of::<(K, V)>()
+            + elements_heap_size_with_limit(self.len(), self.keys(), limit)
+            + elements_heap_size_with_limit(self.len(), self.values(), limit)
+    }
+}
+
+impl<A, S> DataSize for HashSet<A, S>
+where
+    A: Clone + Hash + Eq + DataSize,
+    S: BuildHasher,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.len() * size_of::<A>() + elements_heap_size(self.len(), self.iter())
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.len() * size_of::<A>() + elements_heap_size_with_limit(self.len(), self.iter(), limit)
+    }
+}
+
+impl<K, V> DataSize for OrdMap<K, V>
+where
+    K: Ord + DataSize,
+    V: DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.len() * size_of::<(K, V)>()
+            + elements_heap_size(self.len(), self.keys())
+            + elements_heap_size(self.len(), self.values())
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.len() * size_of::<(K, V)>()
+            + elements_heap_size_with_limit(self.len(), self.keys(), limit)
+            + elements_heap_size_with_limit(self.len(), self.values(), limit)
+    }
+}
+
+impl<A> DataSize for OrdSet<A>
+where
+    A: Ord + DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.len() * size_of::<A>() + elements_heap_size(self.len(), self.iter())
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.len() * size_of::<A>() + elements_heap_size_with_limit(self.len(), self.iter(), limit)
+    }
+}
+
+/// Returns the size of the chunks holding the elements of a `Vector`.
+///
+/// Small vectors keep their elements inline and have no chunks at all.
+#[inline]
+fn vector_storage_size<A: Clone>(vector: &Vector<A>) -> usize {
+    if vector.is_inline() {
+        0
+    } else {
+        vector.len() * size_of::<A>()
+    }
+}
+
+/// Returns the heap data of `len` elements of a persistent collection.
+#[inline]
+fn elements_heap_size<'a, T, I>(len: usize, items: I) -> usize
+where
+    T: DataSize + 'a,
+    I: Iterator<Item = &'a T>,
+{
+    if is_dynamic::<T>() {
+        items.map(DataSize::estimate_heap_size).sum()
+    } else {
+        len * T::STATIC_HEAP_SIZE
+    }
+}
+
+/// Returns the heap data of `len` elements of a persistent collection within a limit.
+#[inline]
+fn elements_heap_size_with_limit<'a, T, I>(len: usize, items: I, limit: &mut Limit) -> usize
+where
+    T: DataSize + 'a,
+    I: Iterator<Item = &'a T>,
+{
+    if is_dynamic::<T>() {
+        sum_with_limit(items, limit)
+    } else {
+        len * T::STATIC_HEAP_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::size_of;
+
+    use im::{HashMap, HashSet, OrdMap, OrdSet, Vector};
+
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_vector() {
+        assert_eq!(data_size(&Vector::<u64>::new()), 0);
+
+        let vector: Vector<u64> = (0..1000).collect();
+        assert_eq!(data_size(&vector), 1000 * size_of::<u64>());
+
+        let strings: Vector<String> = (0..100).map(|i| i.to_string()).collect();
+        assert!(data_size(&strings) > 100 * size_of::<String>());
+
+        // Clones share their chunks, but are counted in full.
+        let copy = strings.clone();
+        assert_eq!(data_size(&copy), data_size(&strings));
+    }
+
+    #[test]
+    fn test_maps_and_sets() {
+        #[derive(DataSize)]
+        struct Index {
+            by_name: HashMap<String, u32>,
+            names: HashSet<String>,
+            by_id: OrdMap<u32, String>,
+            ids: OrdSet<u32>,
+        }
+
+        let empty = Index {
+            by_name: HashMap::new(),
+            names: HashSet::new(),
+            by_id: OrdMap::new(),
+            ids: OrdSet::new(),
+        };
+        assert_eq!(data_size(&empty), 0);
+
+        let index = Index {
+            by_name: HashMap::unit("first".to_owned(), 1),
+            names: HashSet::unit("first".to_owned()),
+            by_id: OrdMap::unit(1, "first".to_owned()),
+            ids: OrdSet::unit(1),
+        };
+        let name = 5 + allocation_overhead(5);
+        assert_eq!(data_size(&index.by_name), size_of::<(String, u32)>() + name);
+        assert_eq!(data_size(&index.names), size_of::<String>() + name);
+        assert_eq!(data_size(&index.by_id), size_of::<(u32, String)>() + name);
+
+        assert_eq!(data_size(&index.ids), size_of::<u32>());
+        assert!(data_size(&index) > 0);
+    }
+}
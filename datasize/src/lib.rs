@@ -19,10 +19,11 @@
 //! guess the size of its heap allocation:
 //!
 //! ```rust
-//! use datasize::data_size;
+//! use datasize::{allocation_overhead, data_size};
 //!
 //! let data: Vec<u64> = vec![1, 2, 3];
-//! assert_eq!(data_size(&data), 24);
+//! // The overhead is zero unless the `alloc-overhead` feature is enabled.
+//! assert_eq!(data_size(&data), 24 + allocation_overhead(24));
 //! ```
 //!
 //! Types implementing the trait also provide two additional constants, `IS_DYNAMIC` and
@@ -47,9 +48,9 @@
 //!
 //!
 //! ```rust
-//! use datasize::DataSize;
+//! use datasize::{allocation_overhead, DataSize};
 //!
-//! assert_eq!(Box::<u64>::STATIC_HEAP_SIZE, 8);
+//! assert_eq!(Box::<u64>::STATIC_HEAP_SIZE, 8 + allocation_overhead(8));
 //! assert!(!Box::<u64>::IS_DYNAMIC);
 //! ```
 //!
@@ -82,12 +83,61 @@
 //! This automatically marks the whole struct as always dynamic, so the custom estimation function
 //! is called every time `MyStruct` is sized.
 //!
+//! Fields annotated with `#[data_size(include_stack)]` additionally count the stack size of their
+//! type, e.g. to report the total footprint of a `Box<[T]>` including its pointer and length.
+//!
+//! The derived `IS_DYNAMIC` can be overridden with `#[data_size(is_dynamic = ...)]` on the type
+//! itself, e.g. when a field that looks dynamic is known to never hold any heap data. Be careful
+//! when setting it to `false`: values of non-dynamic types are sized by their `STATIC_HEAP_SIZE`
+//! alone, so any heap data beyond it is silently not counted.
+//!
+//! Function pointers like `fn(u8) -> u8` implement `DataSize` and never own any heap data. Boxed
+//! closures such as `Box<dyn Fn()>` cannot be inspected, so fields holding them can use the
+//! `fn_ptr_size` helper, which always reports zero:
+//!
+//! ```rust
+//! use datasize::{data_size, fn_ptr_size, DataSize};
+//!
+//! #[derive(DataSize)]
+//! struct Handlers {
+//!     parse: fn(u8) -> u8,
+//!     #[data_size(with = fn_ptr_size)]
+//!     on_change: Box<dyn Fn(u8)>,
+//! }
+//!
+//! let handlers = Handlers {
+//!     parse: |x| x + 1,
+//!     on_change: Box::new(|_| {}),
+//! };
+//! assert_eq!(data_size(&handlers), 0);
+//! ```
+//!
+//! Note that the captures of a boxed closure are stored on the heap, but are not counted.
+//!
+//! # Deriving for unions
+//!
+//! The contents of a union cannot be inspected, so deriving `DataSize` for one requires stating its
+//! heap size explicitly through a `#[data_size(const = ...)]` attribute on the union itself:
+//!
+//! ```rust
+//! use datasize::{data_size, DataSize};
+//!
+//! #[derive(DataSize)]
+//! #[data_size(const = 0)]
+//! union IntOrFloat {
+//!     int: u64,
+//!     float: f64,
+//! }
+//!
+//! assert_eq!(data_size(&IntOrFloat { int: 1 }), 0);
+//! ```
+//!
 //! # Implementing `DataSize` for custom types
 //!
 //! The `DataSize` trait can be implemented for custom types manually:
 //!
 //! ```rust
-//! # use datasize::{DataSize, data_size};
+//! # use datasize::{allocation_overhead, DataSize, data_size};
 //! struct MyType {
 //!     items: Vec<i64>,
 //!     flag: bool,
@@ -114,15 +164,18 @@
 //!     counter: Box::new(42),
 //! };
 //!
-//! // Three i64 and one u64 on the heap sum up to 32 bytes:
-//! assert_eq!(data_size(&my_data), 32);
+//! // Three i64 and one u64 on the heap sum up to 32 bytes, in two allocations:
+//! assert_eq!(
+//!     data_size(&my_data),
+//!     32 + allocation_overhead(24) + allocation_overhead(8)
+//! );
 //! ```
 //!
 //! Since implementing this for `struct` types is cumbersome and repetitive, the crate provides a
 //! `DataSize` macro for convenience:
 //!
 //! ```
-//! # use datasize::{DataSize, data_size};
+//! # use datasize::{allocation_overhead, DataSize, data_size};
 //! // Equivalent to the manual implementation above:
 //! #[derive(DataSize)]
 //! struct MyType {
@@ -135,11 +188,64 @@
 //! #     flag: true,
 //! #     counter: Box::new(42),
 //! # };
-//! # assert_eq!(data_size(&my_data), 32);
+//! # assert_eq!(
+//! #     data_size(&my_data),
+//! #     32 + allocation_overhead(24) + allocation_overhead(8)
+//! # );
 //! ```
 //!
 //! See the `DataSize` macro documentation in the `datasize_derive` crate for details.
 //!
+//! ## Collections and spare capacity
+//!
+//! Collections like `Vec`, `VecDeque` and `String` report their allocated buffer based on their
+//! `capacity`, not their `len`, as reserved but unused space is heap memory all the same:
+//!
+//! ```rust
+//! use datasize::{allocation_overhead, data_size};
+//!
+//! let mut data: Vec<u64> = Vec::with_capacity(10);
+//! data.extend([1, 2, 3]);
+//! # #[cfg(not(feature = "vec-len-sizing"))]
+//! assert_eq!(data_size(&data), 80 + allocation_overhead(80));
+
+//! ```
+//!
+//! The heap data of the elements themselves (e.g. for a `Vec<Vec<u8>>`) is only counted for the
+//! elements actually present.
+//!
+//! If stable numbers are preferred over accurate ones, e.g. in tests or dashboards, the
+//! `vec-len-sizing` feature can be enabled to size the buffers of `Vec`, `VecDeque`, `BinaryHeap`
+//! and `String` by their `len` instead.
+//!
+//! To find over-allocated buffers, the `detailed-spare` feature splits the buffers of `Vec`,
+//! `VecDeque`, `String` and `HashMap` in detailed estimates into a `"used"` node, holding the
+//! bytes of the elements present, and a `"spare"` node, holding the rest of the allocation.
+//!
+//! The `BuildHasher` state of a `HashMap` or `HashSet` is not counted by default, as it rarely
+//! holds any heap data. The `count-hasher-state` feature counts the heap data of hashers
+//! implementing `DataSize`, at the cost of requiring all hashers used in sized maps to implement
+//! it. It is implemented for the hashers of `std`.
+//!
+//! ## Allocator overhead
+//!
+//! By default, only the bytes requested from the allocator are counted. Real allocators add
+//! bookkeeping data and round up requested sizes, which adds up for many small allocations like
+//! `Box<u8>`. Enabling the `alloc-overhead` feature counts an additional `ALLOCATION_OVERHEAD`
+//! bytes for every heap allocation made by the supported types. Manual implementations can use the
+//! `allocation_overhead` function to do the same.
+//!
+//! Alternatively, the `usable-size` feature adds the unsafe `data_size_usable` function, which
+//! asks the allocator for the usable size of the allocations of `Box`, `Vec` and `String` values,
+//! i.e. the requested size including the rounding of the allocator, which matches
+//! `malloc_usable_size`. This is supported on Linux, Android, FreeBSD, macOS and iOS, other
+//! platforms report the requested size. Its caller must guarantee that the global allocator is the
+//! system `malloc` or a replacement exporting its symbols, such as jemalloc with unprefixed
+//! symbols, as the crate cannot check which allocator made an allocation. All safe functions keep
+//! using the requested size. `IS_DYNAMIC` and `STATIC_HEAP_SIZE` are not affected by the feature,
+//! `data_size_usable` estimates every value owning heap data individually instead. Vectors are sized by their requested size if the `vec-len-sizing` or `allocator_api` features
+//! are enabled.
+//!
 //! ## Performance considerations
 //!
 //! Determining the full size of data can be quite expensive, especially if multiple nested levels
@@ -149,6 +255,60 @@
 //! However, if the contained types are dynamic, every element must (and will) be checked, so keep
 //! this in mind when performance is an issue.
 //!
+//! For large structures that are sized often but rarely modified, the `Tracked` wrapper can be used
+//! to cache the estimate until the value is modified.
+//!
+//! If a rough lower bound is sufficient, `data_size_with_limit` can be used to cap the number of
+//! dynamic elements visited:
+//!
+//! ```rust
+//! use datasize::data_size_with_limit;
+//!
+//! let data: Vec<Vec<u8>> = vec![vec![0; 1024]; 1000];
+//! let (estimate, limit_hit) = data_size_with_limit(&data, 10);
+//! assert!(limit_hit);
+//! assert!(estimate >= 10 * 1024);
+//! ```
+//!
+//! Estimating a value recurses once for every level of nested data, which can overflow the stack
+//! for deeply nested structures, e.g. long linked lists of boxes. `data_size_with_depth` stops
+//! descending at a maximum depth instead, returning a lower bound.
+//!
+//! For very large maps whose values are similar in size, `estimate_map_sampled` extrapolates from
+//! a sample of entries instead of visiting all of them.
+//!
+//! ## Custom accounting
+//!
+//! With the `visitor` feature enabled, the heap allocations of a value can be walked through
+//! `DataSize::visit`, which reports every allocation found to a custom `DataSizeVisitor`. This
+//! allows collecting statistics other than the total size, e.g. counting the number of
+//! allocations:
+//!
+//! ```rust
+//! # #[cfg(feature = "visitor")] {
+//! use datasize::{DataSize, DataSizeVisitor};
+//!
+//! struct AllocationCounter(usize);
+//!
+//! impl DataSizeVisitor for AllocationCounter {
+//!     fn enter(&mut self, _bytes: usize, _elements: usize) {
+//!         self.0 += 1;
+//!     }
+//!
+//!     fn leave(&mut self) {}
+//! }
+//!
+//! let data: Vec<Box<u64>> = vec![Box::new(1), Box::new(2)];
+//! let mut counter = AllocationCounter(0);
+//! data.visit(&mut counter);
+//! assert_eq!(counter.0, 3);
+//! # }
+//! ```
+//!
+//! The derive macro and the implementations for `Vec`, `Box` and `Option` report their
+//! allocations individually, any other type is reported as a single allocation of its estimated
+//! heap size.
+//!
 //! ## Handlings references, `Arc`s and similar types
 //!
 //! Any reference will be counted as having a data size of 0, as it does not own the value. There
@@ -156,23 +316,89 @@
 //!
 //! ### `Arc` and `Rc`
 //!
-//! Currently `Arc`s are not supported. A planned development is to allow users to mark an instance
-//! of an `Arc` as "primary" and have its heap memory usage counted, but currently this is not
-//! implemented.
+//! A bare `Arc` is estimated to have a heap size of `0`, as its pointee may be shared by other
+//! pointers. Counting it through every one of them would count it multiple times, or loop forever
+//! on cycles. The field considered the owner of the pointee can opt in to having it counted through
+//! `count_arc`, see below.
+//!
+//! The `Rc` type is handled in the same manner, using `count_rc`.
+//!
+//! With the `count-unique-shared` feature enabled, an `Arc` or `Rc` whose strong count is 1 and
+//! whose weak count is 0 counts the size of its pointee, e.g. the bytes of an `Arc<str>` or the
+//! elements of an `Arc<[T]>`. This applies to shared pointers of every type, not just strings and
+//! slices. Heap data owned by the pointee itself (like the buffers of an `Arc<[Vec<u8>]>`) is not
+//! counted, `count_arc` and `count_rc` can be used for that. A pointer with weak references is
+//! never counted, as the value may be reached through an upgraded weak reference as well.
 //!
-//! Any `Arc` will be estimated to have a heap size of `0`, to avoid cycles resulting in infinite
-//! loops.
+//! A field using `count_arc` or `count_rc` always counts the shared value in full, including its
+//! heap data and the reference counts, however often it is shared:
 //!
-//! The `Rc` type is handled in the same manner.
+//! ```rust
+//! use std::sync::Arc;
+//! use datasize::{count_arc, data_size, DataSize};
+//!
+//! #[derive(DataSize)]
+//! struct Cache {
+//!     #[data_size(with = count_arc)]
+//!     entries: Arc<Vec<u64>>,
+//! }
+//!
+//! let cache = Cache { entries: Arc::new(vec![1, 2, 3]) };
+//! let _view = cache.entries.clone();
+//! assert!(data_size(&cache) > 24);
+//! ```
+//!
+//! Note that every field using these functions is counted, so using them on multiple fields
+//! pointing at the same allocation double counts it.
+//!
+//! Graphs of shared pointers, e.g. of `Rc<RefCell<Node>>`, can be sized with the `GraphSizer`
+//! available through the `graph` feature. It counts every node reachable from a root exactly
+//! once, even through cycles, given a `GraphNode` implementation exposing the children of a node.
+//!
+//! ### Strict mode
+//!
+//! Since a bare `Arc` or `Rc` silently reports no heap data, large shared values are easily
+//! missed. The `strict-shared` feature removes the `DataSize` implementations of `Arc` and `Rc`
+//! altogether, which turns every shared pointer field of a derived type into a compile error. When
+//! enabling it, each such field has to be migrated to either
+//!
+//! * `#[data_size(with = count_arc)]` (or `count_rc`), if the field owns the shared value, or
+//! * `#[data_size(skip)]`, if the value is owned and counted elsewhere.
+//!
+//! Manual implementations call `count_arc` or skip the pointer accordingly. Since it removes
+//! implementations, `strict-shared` is best enabled by the final binary only, and makes
+//! `count-unique-shared` ineffective.
 //!
 //! ## Additional types
 //!
 //! Some additional types from external crates are available behind feature flags.
 //!
+//! * `arrayvec-types`: Support for the `arrayvec::ArrayVec` and `arrayvec::ArrayString` types.
+//! * `bitvec-types`: Support for the bit-packed `bitvec::vec::BitVec` type.
+//! * `chrono-types`: Support for the date and time types of the `chrono` crate.
+//! * `compact_str-types`: Support for the `compact_str::CompactString` type.
 //! * `fake_clock-types`: Support for the `fake_instant::FakeClock` type.
 //! * `futures-types`: Some types from the `futures` crate.
+//! * `glam-types`: Support for the vector, matrix and quaternion types of the `glam` crate.
+//! * `heapless-types`: Support for the fixed capacity collections of the `heapless` crate.
+//! * `im-types`: Support for the persistent collections of the `im` crate. Structure shared
+//!   between clones is counted for each of them.
+//! * `ndarray-types`: Support for the owned `ndarray::Array` and the borrowed view types.
+//! * `ordered-float-types`: Support for the `ordered_float::OrderedFloat` and
+//!   `ordered_float::NotNan` types.
+//! * `petgraph-types`: Support for the `petgraph::graph::Graph` type.
+//! * `regex-types`: Support for the `regex::Regex` type, roughly estimated by its pattern length.
+//! * `serde_json-types`: Support for the `serde_json::Value` type.
+//! * `slab-types`: Support for the `slab::Slab` type, which is sized by its capacity.
 //! * `smallvec-types`: Support for the `smallvec::SmallVec` type.
+//! * `time-types`: Support for the date and time types of the `time` crate.
+//! * `tinyvec-types`: Support for the `tinyvec::TinyVec` and `tinyvec::ArrayVec` types.
 //! * `tokio-types`: Some types from the `tokio` crate.
+//! * `uuid-types`: Support for the `uuid::Uuid` type.
+//!
+//! Types generated by macros in user code, like the flag types of the `bitflags` crate, cannot be
+//! supported this way. As they do not allocate, `impl_zero_heap!` can be used to implement
+//! `DataSize` for them instead.
 //!
 //! ## `no_std` support
 //!
@@ -182,43 +408,114 @@
 //! on the standard library. This can be used to derive the `DataSize` trait for types without
 //! boilerplate, even though their heap size will usually be 0.
 //!
-//! ## Known issues
-//!
-//! The derive macro currently does not support generic structs with inline type bounds, e.g.
+//! Crates that do have an allocator can enable the `alloc` feature instead, which provides the
+//! implementations for the types of the `alloc` crate, like `Vec`, `String`, `Box` or `BTreeMap`,
+//! without requiring `std`. The `std` feature implies `alloc`.
 //!
-//! ```ignore
-//! struct Foo<T: Copy> { ... }
-//! ```
+//! Without any allocator, the fixed capacity collections of the `heapless` crate are supported
+//! through the `heapless-types` feature.
 //!
-//! This can be worked around by using an equivalent `where` clause:
+//! ## Known issues
 //!
-//! ```ignore
-//! struct Foo<T>
-//! where T: Copy
-//! { ... }
-//! ```
+//! Recursive types can only be derived if the recursion passes through a type whose `IS_DYNAMIC`
+//! and `STATIC_HEAP_SIZE` do not depend on its contents, like `Vec<Self>`. Recursing through an
+//! `Option<Box<Self>>` results in a cycle error while evaluating the constants and requires a
+//! manual implementation.
 
-#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 #![allow(clippy::assertions_on_constants)]
 
+#[cfg(feature = "alloc")]
+mod alloc;
+#[cfg(feature = "arrayvec-types")]
+mod arrayvec;
+#[cfg(feature = "bitvec-types")]
+mod bitvec;
+#[cfg(feature = "chrono-types")]
+mod chrono;
+#[cfg(feature = "compact_str-types")]
+mod compact_str;
 #[cfg(feature = "fake_clock-types")]
 mod fake_clock;
 #[cfg(feature = "futures-types")]
 mod futures;
+#[cfg(feature = "glam-types")]
+mod glam;
+#[cfg(feature = "graph")]
+mod graph;
+#[cfg(feature = "heapless-types")]
+mod heapless;
+#[cfg(feature = "im-types")]
+mod im;
+#[cfg(feature = "ndarray-types")]
+mod ndarray;
+#[cfg(feature = "ordered-float-types")]
+mod ordered_float;
+#[cfg(feature = "petgraph-types")]
+mod petgraph;
+#[cfg(feature = "regex-types")]
+mod regex;
+#[cfg(feature = "serde_json-types")]
+mod serde_json;
+mod size;
+#[cfg(feature = "slab-types")]
+mod slab;
 #[cfg(feature = "smallvec-types")]
 mod smallvec;
 #[cfg(feature = "std")]
 mod std;
+#[cfg(feature = "time-types")]
+mod time;
+#[cfg(feature = "tinyvec-types")]
+mod tinyvec;
 #[cfg(feature = "tokio-types")]
 mod tokio;
-
+mod tracked;
+#[cfg(feature = "usable-size")]
+mod usable_size;
+#[cfg(feature = "uuid-types")]
+mod uuid;
+
+#[cfg(feature = "alloc")]
+pub use crate::alloc::{count_arc, count_rc};
+#[cfg(feature = "std")]
+pub use crate::std::{estimate_map_sampled, HasherHeapSize};
 pub use datasize_derive::DataSize;
+#[cfg(feature = "graph")]
+pub use graph::{GraphNode, GraphSizer};
+pub use size::Size;
+pub use tracked::Tracked;
+#[cfg(feature = "usable-size")]
+pub use usable_size::data_size_usable;
 
 /// A `const fn` variant of the `min` function.
 pub const fn min(a: usize, b: usize) -> usize {
     [a, b][(a > b) as usize]
 }
 
+/// The number of bytes an allocator is assumed to add to every heap allocation.
+///
+/// Only counted if the `alloc-overhead` feature is enabled, see `allocation_overhead`.
+pub const ALLOCATION_OVERHEAD: usize = 16;
+
+/// Returns the allocator overhead counted for a single heap allocation of `bytes` bytes.
+///
+/// Real allocators round up allocation sizes and store bookkeeping data alongside them. With the
+/// `alloc-overhead` feature enabled, `ALLOCATION_OVERHEAD` bytes are counted for every allocation
+/// to bring estimates closer to the actual memory usage of a process. Without the feature or for
+/// empty allocations (which do not allocate at all), this is always zero.
+///
+/// Useful when implementing `DataSize` manually for types owning heap allocations.
+#[inline]
+pub const fn allocation_overhead(bytes: usize) -> usize {
+    if cfg!(feature = "alloc-overhead") && bytes > 0 {
+        ALLOCATION_OVERHEAD
+    } else {
+        0
+    }
+}
+
 /// Indicates that a type knows how to approximate its memory usage.
 pub trait DataSize {
     /// If `true`, the type has a heap size that can vary at runtime, depending on the actual value.
@@ -233,6 +530,43 @@ pub trait DataSize {
     /// Does not include data on the stack, which is usually determined using `mem::size_of`.
     fn estimate_heap_size(&self) -> usize;
 
+    /// Estimates the size of heap memory taken up by this value, within a traversal budget.
+    ///
+    /// Similar to `estimate_heap_size`, but every dynamic element of a collection visited consumes
+    /// one node of the `limit`, and no further elements are descended into once it is exhausted.
+    /// Nested data, like the elements of a collection, should be estimated through
+    /// `Limit::descend` to respect the maximum depth of the limit. Implementations without dynamic
+    /// elements can rely on the default, which ignores the limit.
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        let _ = limit;
+        self.estimate_heap_size()
+    }
+
+    #[cfg(feature = "visitor")]
+    /// Walks the heap allocations owned by this value, reporting each to the `visitor`.
+    ///
+    /// Every allocation is reported through a call to `DataSizeVisitor::enter`, followed by the
+    /// visits of the elements stored inside of it and finally a matching `DataSizeVisitor::leave`.
+    /// The default implementation reports the whole `estimate_heap_size` as a single opaque
+    /// allocation, if it is non-zero.
+    ///
+    /// Requires the `visitor` feature to be enabled.
+    #[inline]
+    fn visit<V: DataSizeVisitor>(&self, visitor: &mut V) {
+        visit_opaque(self.estimate_heap_size(), visitor)
+    }
+
+    #[cfg(feature = "detailed")]
+    /// The keys of the top level of the detailed estimate, in declaration order.
+    ///
+    /// Derived implementations list the names (or positions) of all fields not skipped for
+    /// structs, and the qualified names of all variants not skipped for enums, e.g. to pre-size a
+    /// report or to detect renamed fields. Empty by default.
+    ///
+    /// Requires the `detailed` feature to be enabled.
+    const DATA_SIZE_FIELDS: &'static [&'static str] = &[];
+
     #[cfg(feature = "detailed")]
     /// Create a tree of memory estimations.
     ///
@@ -246,9 +580,50 @@ pub trait DataSize {
     }
 }
 
+/// Object safe version of `DataSize`, for sizing values of different types behind trait objects.
+///
+/// Implemented for every type implementing `DataSize`. A `Box<dyn DynDataSize>` implements
+/// `DataSize` itself, counting the boxed value and its heap data:
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use datasize::{allocation_overhead, data_size, DynDataSize};
+///
+/// let values: Vec<Box<dyn DynDataSize>> = vec![Box::new(1u64), Box::new(vec![0u8; 16])];
+/// assert_eq!(
+///     data_size(&values[1]),
+///     24 + allocation_overhead(24) + 16 + allocation_overhead(16)
+/// );
+/// # }
+/// ```
+pub trait DynDataSize {
+    /// Estimates the size of heap memory taken up by this value, see
+    /// `DataSize::estimate_heap_size`.
+    fn dyn_estimate_heap_size(&self) -> usize;
+
+    /// Estimates the size of heap memory taken up by this value within a limit, see
+    /// `DataSize::estimate_heap_size_with_limit`.
+    fn dyn_estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize;
+}
+
+impl<T> DynDataSize for T
+where
+    T: DataSize,
+{
+    #[inline]
+    fn dyn_estimate_heap_size(&self) -> usize {
+        data_size(self)
+    }
+
+    #[inline]
+    fn dyn_estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.estimate_heap_size_with_limit(limit)
+    }
+}
+
 #[cfg(feature = "detailed")]
 /// A node in a memory reporting tree.
-#[derive(Debug, serde::Serialize, PartialEq)]
+#[derive(Clone, Debug, serde::Serialize, PartialEq)]
 pub enum MemUsageNode {
     Size(usize),
     Detailed(::std::collections::HashMap<&'static str, MemUsageNode>),
@@ -264,6 +639,332 @@ impl MemUsageNode {
             MemUsageNode::Detailed(members) => members.values().map(MemUsageNode::total).sum(),
         }
     }
+
+    /// Merges another memory estimation into this one.
+    ///
+    /// Two `Detailed` nodes are merged by recursively summing up the values under identical keys,
+    /// keys only present in `other` are added. `Size` nodes are simply added up. If one node is a
+    /// `Size` and the other one is `Detailed`, both are collapsed into a `Size` of their totals.
+    pub fn merge(&mut self, other: MemUsageNode) {
+        match (self, other) {
+            (MemUsageNode::Detailed(members), MemUsageNode::Detailed(other_members)) => {
+                for (key, node) in other_members {
+                    match members.entry(key) {
+                        ::std::collections::hash_map::Entry::Occupied(mut entry) => {
+                            entry.get_mut().merge(node)
+                        }
+                        ::std::collections::hash_map::Entry::Vacant(entry) => {
+                            entry.insert(node);
+                        }
+                    }
+                }
+            }
+            (this, other) => {
+                *this = MemUsageNode::Size(this.total() + other.total());
+            }
+        }
+    }
+
+    /// Computes the change from this memory estimation to a later one.
+    ///
+    /// Keys present in both of two `Detailed` nodes are compared recursively, keys present in only
+    /// one of them are reported as `Added` or `Removed`. All other pairs of nodes are compared by
+    /// their totals.
+    pub fn diff(&self, later: &MemUsageNode) -> MemUsageDiff {
+        match (self, later) {
+            (MemUsageNode::Detailed(members), MemUsageNode::Detailed(later_members)) => {
+                let mut diff = ::std::collections::HashMap::new();
+                for (key, node) in members {
+                    let change = match later_members.get(key) {
+                        Some(later_node) => node.diff(later_node),
+                        None => MemUsageDiff::Removed(node.total()),
+                    };
+                    diff.insert(*key, change);
+                }
+                for (key, later_node) in later_members {
+                    if !members.contains_key(key) {
+                        diff.insert(*key, MemUsageDiff::Added(later_node.total()));
+                    }
+                }
+                MemUsageDiff::Detailed(diff)
+            }
+            (this, later) => MemUsageDiff::Changed(signed_change(this.total(), later.total())),
+        }
+    }
+
+    /// Collapses every `Detailed` subtree with a total below `threshold` into a single `Size`.
+    ///
+    /// Subtrees at or above the threshold are kept, with their members pruned in turn. The total of
+    /// the tree does not change.
+    pub fn prune(&mut self, threshold: usize) {
+        if let MemUsageNode::Detailed(members) = self {
+            let total = members.values().map(MemUsageNode::total).sum();
+            if total < threshold {
+                *self = MemUsageNode::Size(total);
+            } else {
+                for node in members.values_mut() {
+                    node.prune(threshold);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the signed change from `before` to `after`, saturating at the bounds of `isize`.
+#[cfg(feature = "detailed")]
+#[inline]
+fn signed_change(before: usize, after: usize) -> isize {
+    if after >= before {
+        (after - before).min(isize::MAX as usize) as isize
+    } else {
+        -((before - after).min(isize::MAX as usize) as isize)
+    }
+}
+
+#[cfg(feature = "detailed")]
+/// The difference between two memory reporting trees, created by `MemUsageNode::diff`.
+#[derive(Clone, Debug, serde::Serialize, PartialEq)]
+pub enum MemUsageDiff {
+    /// The signed change in bytes of a node present in both trees.
+    Changed(isize),
+    /// A node only present in the later tree, with its total size.
+    Added(usize),
+    /// A node only present in the earlier tree, with its total size.
+    Removed(usize),
+    /// The differences of the members of two detailed nodes.
+    Detailed(::std::collections::HashMap<&'static str, MemUsageDiff>),
+}
+
+#[cfg(feature = "detailed")]
+impl MemUsageDiff {
+    /// Calculate the total change in bytes.
+    #[inline]
+    pub fn total(&self) -> isize {
+        match self {
+            MemUsageDiff::Changed(change) => *change,
+            MemUsageDiff::Added(size) => signed_change(0, *size),
+            MemUsageDiff::Removed(size) => signed_change(*size, 0),
+            MemUsageDiff::Detailed(members) => members.values().map(MemUsageDiff::total).sum(),
+        }
+    }
+}
+
+/// A traversal budget used when estimating heap size through [`data_size_with_limit`] or
+/// [`data_size_with_depth`].
+#[derive(Debug)]
+pub struct Limit {
+    /// The number of dynamic elements that may still be visited.
+    remaining_nodes: usize,
+    /// The number of levels of nested data that may still be descended into.
+    remaining_depth: usize,
+    /// Whether or not visiting an element was refused due to the budget being exhausted.
+    exceeded: bool,
+}
+
+impl Limit {
+    /// Creates a new limit allowing at most `max_nodes` dynamic elements to be visited.
+    #[inline]
+    pub fn new(max_nodes: usize) -> Self {
+        Limit::with_depth(max_nodes, usize::MAX)
+    }
+
+    /// Creates a new limit allowing at most `max_nodes` dynamic elements to be visited, descending
+    /// at most `max_depth` levels into nested data.
+    #[inline]
+    pub fn with_depth(max_nodes: usize, max_depth: usize) -> Self {
+        Limit {
+            remaining_nodes: max_nodes,
+            remaining_depth: max_depth,
+            exceeded: false,
+        }
+    }
+
+    /// Consumes a single node of the budget before visiting a dynamic element.
+    ///
+    /// Returns `false` if the budget is exhausted, in which case the element must not be visited.
+    #[inline]
+    pub fn consume_node(&mut self) -> bool {
+        if self.remaining_nodes == 0 {
+            self.exceeded = true;
+            false
+        } else {
+            self.remaining_nodes -= 1;
+            true
+        }
+    }
+
+    /// Descends one level into nested data, e.g. the elements of a collection or a boxed value,
+    /// and estimates it using `estimate`.
+    ///
+    /// If the maximum depth has been reached, `estimate` is not called and `0` is returned instead.
+    #[inline]
+    pub fn descend<F>(&mut self, estimate: F) -> usize
+    where
+        F: FnOnce(&mut Limit) -> usize,
+    {
+        if self.remaining_depth == 0 {
+            self.exceeded = true;
+            return 0;
+        }
+
+        self.remaining_depth -= 1;
+        let size = estimate(self);
+        self.remaining_depth += 1;
+        size
+    }
+
+    /// Returns whether or not any element was left out due to the budget being exhausted.
+    #[inline]
+    pub fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+}
+
+#[cfg(feature = "visitor")]
+/// Custom accounting of the heap allocations walked by `DataSize::visit`.
+///
+/// Calls to `enter` and `leave` are always balanced and nest according to ownership, e.g. the
+/// allocation of a `Box` inside a `Vec` is entered after the buffer of the `Vec` and left before
+/// it.
+pub trait DataSizeVisitor {
+    /// Called when entering a heap allocation of `bytes` bytes, storing `elements` elements.
+    ///
+    /// Allocations whose contents are not inspected report `0` elements.
+    fn enter(&mut self, bytes: usize, elements: usize);
+
+    /// Called when leaving the most recently entered allocation.
+    fn leave(&mut self);
+}
+
+#[cfg(feature = "visitor")]
+/// Reports `bytes` of heap memory of unknown structure to a visitor as a single allocation.
+///
+/// Nothing is reported if `bytes` is zero. Useful when implementing `DataSize::visit` manually.
+#[inline]
+pub fn visit_opaque<V: DataSizeVisitor>(bytes: usize, visitor: &mut V) {
+    if bytes > 0 {
+        visitor.enter(bytes, 0);
+        visitor.leave();
+    }
+}
+
+/// Returns whether values of `T` have to be estimated individually, instead of being sized by
+/// their `STATIC_HEAP_SIZE`.
+///
+/// This is `T::IS_DYNAMIC`, except during `data_size_usable`: the usable size of the allocations
+/// owned by a value is only known at runtime, so any value owning heap data is estimated.
+#[cfg(feature = "usable-size")]
+#[inline]
+pub(crate) fn is_dynamic<T: DataSize + ?Sized>() -> bool {
+    T::IS_DYNAMIC || (T::STATIC_HEAP_SIZE > 0 && usable_size::enabled())
+}
+
+#[cfg(not(feature = "usable-size"))]
+#[inline]
+pub(crate) fn is_dynamic<T: DataSize + ?Sized>() -> bool {
+    T::IS_DYNAMIC
+}
+
+/// Sums up the limited heap size estimates of all dynamic `items`.
+///
+/// Stops at the first item that exceeds the budget. The items are nested one level below their
+/// container, unless there are none.
+#[inline]
+fn sum_with_limit<'a, T, I>(items: I, limit: &mut Limit) -> usize
+where
+    T: DataSize + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut items = items.into_iter().peekable();
+    if items.peek().is_none() {
+        return 0;
+    }
+
+    limit.descend(|limit| {
+        let mut size = 0;
+        for item in items {
+            if !limit.consume_node() {
+                break;
+            }
+            size += item.estimate_heap_size_with_limit(limit);
+        }
+        size
+    })
+}
+
+/// Aggregates the detailed estimates of all `items` into a single node.
+///
+/// The trees of all items are merged, so the result describes all items at once without growing
+/// with their number.
+#[cfg(feature = "detailed")]
+#[inline]
+fn detailed_aggregate<'a, T, I>(items: I) -> MemUsageNode
+where
+    T: DataSize + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    // Items without any heap data have no details to report.
+    if !T::IS_DYNAMIC && T::STATIC_HEAP_SIZE == 0 {
+        return MemUsageNode::Size(0);
+    }
+
+    let mut items = items.into_iter();
+    let mut aggregate = match items.next() {
+        Some(first) => first.estimate_detailed_heap_size(),
+        None => return MemUsageNode::Size(0),
+    };
+    for item in items {
+        aggregate.merge(item.estimate_detailed_heap_size());
+    }
+    aggregate
+}
+
+/// Creates a detailed estimate of an allocation of `storage` bytes owning `contents`.
+///
+/// If none of the `contents` have any details, a plain `Size` is returned instead.
+#[cfg(feature = "detailed")]
+#[inline]
+fn detailed_storage(
+    storage_key: &'static str,
+    storage: usize,
+    contents: ::std::vec::Vec<(&'static str, MemUsageNode)>,
+) -> MemUsageNode {
+    if contents
+        .iter()
+        .all(|(_, node)| matches!(node, MemUsageNode::Size(_)))
+    {
+        let total = contents.iter().map(|(_, node)| node.total()).sum::<usize>();
+        return MemUsageNode::Size(storage + total);
+    }
+
+    let mut members = ::std::collections::HashMap::new();
+    members.insert(storage_key, MemUsageNode::Size(storage));
+    members.extend(contents);
+    MemUsageNode::Detailed(members)
+}
+
+/// Creates a detailed estimate of a buffer of `storage` bytes, `used` of which hold elements.
+///
+/// With the `detailed-spare` feature, the buffer is split into a `"used"` and a `"spare"` node,
+/// the latter holding everything allocated beyond the elements, e.g. unused capacity. Otherwise
+/// this is the same as `detailed_storage`.
+#[cfg(feature = "detailed")]
+#[inline]
+fn detailed_buffer(
+    storage_key: &'static str,
+    storage: usize,
+    used: usize,
+    contents: ::std::vec::Vec<(&'static str, MemUsageNode)>,
+) -> MemUsageNode {
+    if !cfg!(feature = "detailed-spare") {
+        return detailed_storage(storage_key, storage, contents);
+    }
+
+    let mut members = ::std::collections::HashMap::new();
+    members.insert("used", MemUsageNode::Size(used.min(storage)));
+    members.insert("spare", MemUsageNode::Size(storage.saturating_sub(used)));
+    members.extend(contents);
+    MemUsageNode::Detailed(members)
 }
 
 /// Estimates allocated heap data from data of value.
@@ -271,6 +972,7 @@ impl MemUsageNode {
 /// Checks if `T` is dynamic; if it is not, returns `T::STATIC_HEAP_SIZE`. Otherwise delegates to
 /// `T::estimate_heap_size`.
 #[inline]
+#[allow(clippy::multiple_bound_locations)]
 pub fn data_size<T: ?Sized>(value: &T) -> usize
 where
     T: DataSize,
@@ -278,9 +980,109 @@ where
     value.estimate_heap_size()
 }
 
+/// Estimates the heap data owned by the value behind a reference.
+///
+/// A reference never owns any heap data, so a `&Box<u64>` stored in a field or collection
+/// contributes nothing to the estimate of its container. This function instead sizes the referenced
+/// value itself, as if it was owned.
+///
+/// For a `reference: &Box<u64>`, `owned_data_size(reference)` is equivalent to
+/// `data_size(reference)` and counts the boxed `u64`. Taking another reference, as in
+/// `data_size(&reference)`, sizes the `&Box<u64>` through the impl for references instead, which
+/// reports zero. The function makes the first case explicit, so it cannot silently turn into the
+/// second.
+#[inline]
+pub fn owned_data_size<T>(value: &T) -> usize
+where
+    T: DataSize + ?Sized,
+{
+    value.estimate_heap_size()
+}
+
+/// Estimates the heap size of a closure or function, which is always zero.
+///
+/// Meant to be used through `#[data_size(with = fn_ptr_size)]` on fields holding closures, e.g. a
+/// `Box<dyn Fn()>`, which do not implement `DataSize`. Captured data is not counted, so the result
+/// is only a lower bound for boxed closures.
+#[inline]
+pub fn fn_ptr_size<F: ?Sized>(_: &F) -> usize {
+    0
+}
+
+/// Estimates allocated heap data from data of value, returned as a human readable `Size`.
+///
+/// Equivalent to `Size(data_size(value))`, e.g. for printing: `println!("{}", data_size_human(&value))`.
+#[inline]
+pub fn data_size_human<T>(value: &T) -> Size
+where
+    T: DataSize + ?Sized,
+{
+    Size(value.estimate_heap_size())
+}
+
+/// Estimates the total heap data allocated by all items of an iterator.
+///
+/// Equivalent to summing up `data_size` of every item, without having to collect them first. If
+/// `T` is not dynamic and the iterator reports an exact length through its `size_hint`, the
+/// items are not visited at all.
+#[inline]
+pub fn data_size_of_iter<'a, T, I>(iter: I) -> usize
+where
+    T: DataSize + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let iter = iter.into_iter();
+
+    if !is_dynamic::<T>() {
+        if let (lower, Some(upper)) = iter.size_hint() {
+            if lower == upper {
+                return lower * T::STATIC_HEAP_SIZE;
+            }
+        }
+    }
+
+    iter.map(DataSize::estimate_heap_size).sum()
+}
+
+/// Estimates allocated heap data from data of value, visiting at most `max_nodes` dynamic
+/// elements.
+///
+/// Useful to cap the cost of sizing deeply nested or very large dynamic structures. The second
+/// value returned indicates whether the limit was hit. If it was, the estimate only covers the
+/// elements visited up to that point and is a lower bound of the actual heap size. Elements that
+/// are not dynamic are sized without being visited and thus never consume any of the budget.
+#[inline]
+pub fn data_size_with_limit<T>(value: &T, max_nodes: usize) -> (usize, bool)
+where
+    T: DataSize + ?Sized,
+{
+    let mut limit = Limit::new(max_nodes);
+    let size = value.estimate_heap_size_with_limit(&mut limit);
+    (size, limit.exceeded())
+}
+
+/// Estimates allocated heap data from data of value, descending at most `max_depth` levels into
+/// nested data.
+///
+/// Every boxed value and the elements of every collection are one level below their container.
+/// Sizing a deeply nested structure, like a long linked list of boxes, recurses once per level and
+/// may overflow the stack; this stops at `max_depth` instead. The second value returned indicates
+/// whether the depth was exceeded, in which case the estimate is a lower bound that only covers the
+/// levels visited.
+#[inline]
+pub fn data_size_with_depth<T>(value: &T, max_depth: usize) -> (usize, bool)
+where
+    T: DataSize + ?Sized,
+{
+    let mut limit = Limit::with_depth(usize::MAX, max_depth);
+    let size = value.estimate_heap_size_with_limit(&mut limit);
+    (size, limit.exceeded())
+}
+
 #[cfg(feature = "detailed")]
 /// Estimates allocated heap data from data of value.
 #[inline]
+#[allow(clippy::multiple_bound_locations)]
 pub fn data_size_detailed<T: ?Sized>(value: &T) -> MemUsageNode
 where
     T: DataSize,
@@ -288,6 +1090,53 @@ where
     value.estimate_detailed_heap_size()
 }
 
+#[cfg(feature = "detailed")]
+/// Estimates allocated heap data from data of value, broken down by the leaves of its detailed
+/// estimate.
+///
+/// Every entry holds the path of a leaf node, with keys joined by `.` (e.g. `"inner.items"`), and
+/// its size. The entries are sorted by size in descending order, so the largest contributors come
+/// first. A value without any details results in a single entry with an empty path.
+pub fn data_size_breakdown<T>(value: &T) -> ::std::vec::Vec<(::std::string::String, usize)>
+where
+    T: DataSize + ?Sized,
+{
+    let mut entries = ::std::vec::Vec::new();
+    flatten_detailed(
+        &value.estimate_detailed_heap_size(),
+        ::std::string::String::new(),
+        &mut entries,
+    );
+
+    // Ties are ordered by path to keep the result independent of the iteration order of the tree.
+    entries.sort_by(|(path_a, size_a), (path_b, size_b)| {
+        size_b.cmp(size_a).then_with(|| path_a.cmp(path_b))
+    });
+    entries
+}
+
+#[cfg(feature = "detailed")]
+/// Appends the leaves of `node` to `entries`, with their keys appended to `path`.
+fn flatten_detailed(
+    node: &MemUsageNode,
+    path: ::std::string::String,
+    entries: &mut ::std::vec::Vec<(::std::string::String, usize)>,
+) {
+    match node {
+        MemUsageNode::Size(size) => entries.push((path, *size)),
+        MemUsageNode::Detailed(members) => {
+            for (key, member) in members {
+                let member_path = if path.is_empty() {
+                    ::std::string::String::from(*key)
+                } else {
+                    ::std::format!("{}.{}", path, key)
+                };
+                flatten_detailed(member, member_path, entries);
+            }
+        }
+    }
+}
+
 /// Helper macro to define a heap size for one or more non-dynamic types.
 #[macro_export]
 macro_rules! non_dynamic_const_heap_size {
@@ -304,6 +1153,187 @@ macro_rules! non_dynamic_const_heap_size {
     };
 }
 
+/// Implements `DataSize` for one or more types that never allocate on the heap.
+///
+/// This is most useful for plain data types from other crates that cannot derive `DataSize`, e.g.
+/// the flag types generated by the `bitflags!` macro, which are newtypes over an integer:
+///
+/// ```rust
+/// # use datasize::data_size;
+/// # struct Permissions(u32);
+/// # struct Mode(u8);
+/// datasize::impl_zero_heap!(Permissions, Mode);
+///
+/// assert_eq!(data_size(&Permissions(0o755)), 0);
+/// ```
+#[macro_export]
+macro_rules! impl_zero_heap {
+    ($($ty:ty),+ $(,)?) => {
+        $(impl $crate::DataSize for $ty {
+            const IS_DYNAMIC: bool = false;
+            const STATIC_HEAP_SIZE: usize = 0;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                0
+            }
+        })+
+    };
+}
+
+/// Implements `DataSize` for a wrapper type by forwarding to one of its fields.
+///
+/// All type parameters of the wrapper are required to implement `DataSize`. The field can be given
+/// by name or by position:
+///
+/// ```rust
+/// # use datasize::{allocation_overhead, data_size};
+/// struct Meters(Vec<f64>);
+/// struct Labeled<T> {
+///     value: T,
+///     label: &'static str,
+/// }
+///
+/// datasize::impl_forwarding!(Meters => .0);
+/// datasize::impl_forwarding!(Labeled<T> => .value);
+///
+/// let wrapped = Labeled { value: Meters(vec![1.0, 2.5]), label: "distance" };
+/// assert_eq!(data_size(&wrapped), 16 + allocation_overhead(16));
+/// ```
+///
+/// Other fields of the wrapper are ignored entirely, use the derive macro if they should be
+/// counted as well.
+#[macro_export]
+macro_rules! impl_forwarding {
+    ($ty:ident $(<$($param:ident),+ $(,)?>)? => . $field:tt) => {
+        impl$(<$($param: $crate::DataSize),+>)? $crate::DataSize for $ty$(<$($param),+>)? {
+            const IS_DYNAMIC: bool = $crate::__field_is_dynamic(|outer: &Self| &outer.$field);
+
+            const STATIC_HEAP_SIZE: usize =
+                $crate::__field_static_heap_size(|outer: &Self| &outer.$field);
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                $crate::DataSize::estimate_heap_size(&self.$field)
+            }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut $crate::Limit) -> usize {
+                $crate::DataSize::estimate_heap_size_with_limit(&self.$field, limit)
+            }
+        }
+    };
+}
+
+/// Implements `DataSize` for one or more types by forwarding to the target they `Deref` to.
+///
+/// Requires the `deref-impls` feature. Only the `Deref::Target` is sized, so it must be the value
+/// owning the heap data. A wrapper around a `Box<u64>` should dereference to the `Box<u64>` itself
+/// to have the boxed value counted, dereferencing to the `u64` inside the box reports no heap data
+/// at all:
+///
+/// ```rust
+/// # #[cfg(feature = "deref-impls")] {
+/// use std::ops::Deref;
+/// use datasize::{allocation_overhead, data_size};
+///
+/// struct Counter(Box<u64>);
+///
+/// impl Deref for Counter {
+///     type Target = Box<u64>;
+///
+///     fn deref(&self) -> &Box<u64> {
+///         &self.0
+///     }
+/// }
+///
+/// datasize::impl_datasize_via_deref!(Counter);
+///
+/// assert_eq!(data_size(&Counter(Box::new(1))), 8 + allocation_overhead(8));
+/// # }
+/// ```
+#[cfg(feature = "deref-impls")]
+#[macro_export]
+macro_rules! impl_datasize_via_deref {
+    ($($ty:ty),+ $(,)?) => {
+        $(impl $crate::DataSize for $ty {
+            const IS_DYNAMIC: bool =
+                <<$ty as ::core::ops::Deref>::Target as $crate::DataSize>::IS_DYNAMIC;
+
+            const STATIC_HEAP_SIZE: usize =
+                <<$ty as ::core::ops::Deref>::Target as $crate::DataSize>::STATIC_HEAP_SIZE;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                $crate::DataSize::estimate_heap_size(::core::ops::Deref::deref(self))
+            }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut $crate::Limit) -> usize {
+                $crate::DataSize::estimate_heap_size_with_limit(
+                    ::core::ops::Deref::deref(self),
+                    limit,
+                )
+            }
+        })+
+    };
+}
+
+/// Returns `IS_DYNAMIC` of the field selected by `accessor`, used by `impl_forwarding!`.
+#[doc(hidden)]
+#[inline]
+pub const fn __field_is_dynamic<S: ?Sized, T: DataSize + ?Sized>(_accessor: fn(&S) -> &T) -> bool {
+    T::IS_DYNAMIC
+}
+
+/// Returns `STATIC_HEAP_SIZE` of the field selected by `accessor`, used by `impl_forwarding!`.
+#[doc(hidden)]
+#[inline]
+pub const fn __field_static_heap_size<S: ?Sized, T: DataSize + ?Sized>(
+    _accessor: fn(&S) -> &T,
+) -> usize {
+    T::STATIC_HEAP_SIZE
+}
+
+/// Adds the stack size of a field to its detailed estimate, used by `#[data_size(include_stack)]`.
+///
+/// Fields with details report it under an additional `"stack"` key.
+#[cfg(feature = "detailed")]
+#[doc(hidden)]
+#[inline]
+pub fn __detailed_with_stack(node: MemUsageNode, stack_size: usize) -> MemUsageNode {
+    match node {
+        MemUsageNode::Size(size) => MemUsageNode::Size(size + stack_size),
+        MemUsageNode::Detailed(mut members) => {
+            members
+                .entry("stack")
+                .or_insert(MemUsageNode::Size(0))
+                .merge(MemUsageNode::Size(stack_size));
+            MemUsageNode::Detailed(members)
+        }
+    }
+}
+
+/// Asserts at compile time that the `STATIC_HEAP_SIZE` of a type has the expected value.
+///
+/// Compilation fails if it does not, which guards against changes to the type (or its fields)
+/// silently altering the constant:
+///
+/// ```rust
+/// datasize::assert_static_heap_size!(Box<u64>, 8 + datasize::allocation_overhead(8));
+/// datasize::assert_static_heap_size!(Vec<u64>, 0);
+/// ```
+#[macro_export]
+macro_rules! assert_static_heap_size {
+    ($ty:ty, $expected:expr) => {
+        #[allow(clippy::assertions_on_constants)]
+        const _: () = assert!(
+            <$ty as $crate::DataSize>::STATIC_HEAP_SIZE == $expected,
+            concat!("unexpected `STATIC_HEAP_SIZE` of `", stringify!($ty), "`")
+        );
+    };
+}
+
 // Hack to allow `+` to be used to join macro arguments.
 macro_rules! strip_plus {
     (+ $($rest: tt)*) => {
@@ -318,46 +1348,69 @@ macro_rules! tuple_heap_size {
         {
             const IS_DYNAMIC: bool = $($name::IS_DYNAMIC)|*;
 
-            const STATIC_HEAP_SIZE: usize =
-                strip_plus!($(+ $name::STATIC_HEAP_SIZE)+);
+            // Saturates instead of overflowing for pathological types.
+            const STATIC_HEAP_SIZE: usize = 0usize$(.saturating_add($name::STATIC_HEAP_SIZE))+;
 
             #[inline]
             fn estimate_heap_size(&self) -> usize {
                 strip_plus!($(+ self.$n.estimate_heap_size())+)
             }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+                strip_plus!($(+ self.$n.estimate_heap_size_with_limit(limit))+)
+            }
         }
     };
 }
 
-macro_rules! array_heap_size {
-    ($($n:tt)+) => {
-        $(
-        impl<T> DataSize for [T; $n]
-        where
-            T: DataSize,
-        {
-            const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+// Primitives
+non_dynamic_const_heap_size!(() u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize bool char f32 f64, 0);
 
-            const STATIC_HEAP_SIZE: usize = T::STATIC_HEAP_SIZE * $n;
+// Assorted heapless `core` types
+non_dynamic_const_heap_size!(
+    core::time::Duration
+    core::cmp::Ordering
+    core::convert::Infallible
+    core::num::FpCategory,
+    0
+);
+
+// Function pointers only point at code, which is never on the heap. Function item types cannot be
+// named, but coerce to the matching function pointer, e.g. when stored in a field.
+macro_rules! fn_ptr_heap_size {
+    ($($name:ident),*) => {
+        fn_ptr_heap_size!(@impl fn($($name),*) -> Ret; $($name),*);
+        fn_ptr_heap_size!(@impl unsafe fn($($name),*) -> Ret; $($name),*);
+        fn_ptr_heap_size!(@impl extern "C" fn($($name),*) -> Ret; $($name),*);
+        fn_ptr_heap_size!(@impl unsafe extern "C" fn($($name),*) -> Ret; $($name),*);
+    };
+    (@impl $ty:ty; $($name:ident),*) => {
+        impl<Ret, $($name),*> DataSize for $ty {
+            const IS_DYNAMIC: bool = false;
+            const STATIC_HEAP_SIZE: usize = 0;
 
             #[inline]
             fn estimate_heap_size(&self) -> usize {
-                if T::IS_DYNAMIC {
-                    (&self[..]).iter().map(DataSize::estimate_heap_size).sum()
-                } else {
-                    T::STATIC_HEAP_SIZE * $n
-                }
+                0
             }
         }
-        )*
     };
 }
 
-// Primitives
-non_dynamic_const_heap_size!(() u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize bool char f32 f64, 0);
-
-// Assorted heapless `core` types
-non_dynamic_const_heap_size!(core::time::Duration, 0);
+fn_ptr_heap_size!();
+fn_ptr_heap_size!(A0);
+fn_ptr_heap_size!(A0, A1);
+fn_ptr_heap_size!(A0, A1, A2);
+fn_ptr_heap_size!(A0, A1, A2, A3);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4, A5);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4, A5, A6);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4, A5, A6, A7);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4, A5, A6, A7, A8);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4, A5, A6, A7, A8, A9);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
+fn_ptr_heap_size!(A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
 
 tuple_heap_size!(0 T0; 1 T1);
 tuple_heap_size!(0 T0; 1 T1; 2 T2);
@@ -384,15 +1437,92 @@ where
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        self.0.estimate_heap_size()
+        self.0.estimate_heap_size()
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.0.estimate_heap_size_with_limit(limit)
+    }
+}
+
+impl<T, const N: usize> DataSize for [T; N]
+where
+    T: DataSize,
+{
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+
+    // Saturates instead of overflowing for pathological types.
+    const STATIC_HEAP_SIZE: usize = T::STATIC_HEAP_SIZE.saturating_mul(N);
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // Only dynamic elements need to be visited, otherwise the size is known upfront. This also
+        // covers `N == 0` without touching any element.
+        if is_dynamic::<T>() {
+            self.iter().map(DataSize::estimate_heap_size).sum()
+        } else {
+            Self::STATIC_HEAP_SIZE
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if is_dynamic::<T>() {
+            sum_with_limit(self, limit)
+        } else {
+            Self::STATIC_HEAP_SIZE
+        }
+    }
+
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        // Arrays are stored inline, so they consist only of their elements.
+        detailed_aggregate(self)
+    }
+}
+
+// A slice does not own the memory it occupies, it is always borrowed or owned by a container like
+// `Box<[T]>`, which counts it. Like an array, the slice itself only accounts for the heap data of
+// its elements, so a `&[u8]` reports nothing.
+impl<T> DataSize for [T]
+where
+    T: DataSize,
+{
+    // The length is only known at runtime, so any element with heap data makes the slice dynamic.
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC || T::STATIC_HEAP_SIZE > 0;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        if is_dynamic::<T>() {
+            self.iter().map(DataSize::estimate_heap_size).sum()
+        } else {
+            T::STATIC_HEAP_SIZE.saturating_mul(self.len())
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if is_dynamic::<T>() {
+            sum_with_limit(self, limit)
+        } else {
+            T::STATIC_HEAP_SIZE.saturating_mul(self.len())
+        }
     }
-}
 
-array_heap_size!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64 128 192 256 384 512 1024 2048 4096 8192 16384 1048576 2097152 3145728 4194304);
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        detailed_aggregate(self)
+    }
+}
 
 // REFERENCES
 
-impl<T> DataSize for &T {
+impl<T: ?Sized> DataSize for &T {
     const IS_DYNAMIC: bool = false;
 
     const STATIC_HEAP_SIZE: usize = 0;
@@ -403,7 +1533,7 @@ impl<T> DataSize for &T {
     }
 }
 
-impl<T> DataSize for &mut T {
+impl<T: ?Sized> DataSize for &mut T {
     const IS_DYNAMIC: bool = false;
 
     const STATIC_HEAP_SIZE: usize = 0;
@@ -420,7 +1550,8 @@ impl<T> DataSize for Option<T>
 where
     T: DataSize,
 {
-    // Options are only not dynamic if their type has no heap data at all and is not dynamic.
+    // Options are only not dynamic if their type has no heap data at all and is not dynamic. A value
+    // with a fixed amount of heap data still makes the option dynamic, since `None` has none.
     const IS_DYNAMIC: bool = (T::IS_DYNAMIC || T::STATIC_HEAP_SIZE > 0);
 
     const STATIC_HEAP_SIZE: usize = 0;
@@ -432,6 +1563,22 @@ where
             None => 0,
         }
     }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        match self {
+            Some(val) => val.estimate_heap_size_with_limit(limit),
+            None => 0,
+        }
+    }
+
+    #[cfg(feature = "visitor")]
+    #[inline]
+    fn visit<V: DataSizeVisitor>(&self, visitor: &mut V) {
+        if let Some(val) = self {
+            val.visit(visitor)
+        }
+    }
 }
 
 impl<T, E> DataSize for Result<T, E>
@@ -452,6 +1599,34 @@ where
             Err(err) => data_size(err),
         }
     }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        match self {
+            Ok(val) => val.estimate_heap_size_with_limit(limit),
+            Err(err) => err.estimate_heap_size_with_limit(limit),
+        }
+    }
+}
+
+impl<T: DataSize> DataSize for core::num::Wrapping<T> {
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+    const STATIC_HEAP_SIZE: usize = T::STATIC_HEAP_SIZE;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.0.estimate_heap_size()
+    }
+}
+
+impl<T: DataSize> DataSize for core::num::Saturating<T> {
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+    const STATIC_HEAP_SIZE: usize = T::STATIC_HEAP_SIZE;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.0.estimate_heap_size()
+    }
 }
 
 impl<T> DataSize for core::marker::PhantomData<T> {
@@ -464,6 +1639,34 @@ impl<T> DataSize for core::marker::PhantomData<T> {
     }
 }
 
+impl<P> DataSize for core::pin::Pin<P>
+where
+    P: DataSize,
+{
+    // `Pin` is a transparent wrapper, its heap size is that of the pointer it wraps.
+    const IS_DYNAMIC: bool = P::IS_DYNAMIC;
+    const STATIC_HEAP_SIZE: usize = P::STATIC_HEAP_SIZE;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        pinned_pointer(self).estimate_heap_size()
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        pinned_pointer(self).estimate_heap_size_with_limit(limit)
+    }
+}
+
+/// Returns a shared reference to the pointer wrapped by a `Pin`.
+#[inline]
+fn pinned_pointer<P>(pin: &core::pin::Pin<P>) -> &P {
+    // SAFETY: `Pin<P>` is `repr(transparent)` over `P`, so the cast is valid. Only a shared
+    //         reference to the pointer itself is handed out, through which the pinned value cannot
+    //         be moved; this is the same access `Pin` uses to implement `Deref`.
+    unsafe { &*(pin as *const core::pin::Pin<P> as *const P) }
+}
+
 impl<T: DataSize> DataSize for core::ops::Range<T> {
     const IS_DYNAMIC: bool = T::IS_DYNAMIC;
     const STATIC_HEAP_SIZE: usize = 2 * T::STATIC_HEAP_SIZE;
@@ -472,12 +1675,18 @@ impl<T: DataSize> DataSize for core::ops::Range<T> {
     fn estimate_heap_size(&self) -> usize {
         self.start.estimate_heap_size() + self.end.estimate_heap_size()
     }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.start.estimate_heap_size_with_limit(limit)
+            + self.end.estimate_heap_size_with_limit(limit)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate as datasize; // Required for the derive macro.
-    use crate::{data_size, DataSize};
+    use crate::{allocation_overhead, data_size, DataSize};
 
     #[test]
     fn test_for_simple_builtin_types() {
@@ -486,6 +1695,235 @@ mod tests {
         assert_eq!(1u16.estimate_heap_size(), 0);
     }
 
+    #[test]
+    fn test_option_is_dynamic() {
+        /// Owns a fixed amount of heap data, like a `Box` of a sized value.
+        struct Fixed;
+
+        impl DataSize for Fixed {
+            const IS_DYNAMIC: bool = false;
+            const STATIC_HEAP_SIZE: usize = 16;
+
+            fn estimate_heap_size(&self) -> usize {
+                16
+            }
+        }
+
+        // Neither dynamic nor any heap data: `None` and `Some` are the same.
+        assert!(!Option::<u64>::IS_DYNAMIC);
+        assert_eq!(Option::<u64>::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&Some(1u64)), 0);
+
+        // Static heap data only exists for `Some`, so the option is dynamic with no static size.
+        assert!(!Fixed::IS_DYNAMIC);
+        assert!(Option::<Fixed>::IS_DYNAMIC);
+        assert_eq!(Option::<Fixed>::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&None::<Fixed>), 0);
+        assert_eq!(data_size(&Some(Fixed)), 16);
+
+        // Dynamic values stay dynamic.
+        assert!(Option::<Vec<u8>>::IS_DYNAMIC);
+        assert_eq!(Option::<Vec<u8>>::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&None::<Vec<u8>>), 0);
+        assert_eq!(data_size(&Some(vec![0u8; 4])), 4 + allocation_overhead(4));
+
+        // Nesting does not change the outcome.
+        assert!(!Option::<Option<u64>>::IS_DYNAMIC);
+        assert!(Option::<Option<Fixed>>::IS_DYNAMIC);
+        assert_eq!(data_size(&Some(None::<Fixed>)), 0);
+        assert_eq!(data_size(&Some(Some(Fixed))), 16);
+    }
+
+    #[test]
+    fn test_impl_zero_heap() {
+        /// Mimics the newtype generated by `bitflags!`.
+        #[allow(dead_code)]
+        #[derive(Clone, Copy)]
+        struct Flags(u32);
+
+        #[allow(dead_code)]
+        struct Other(u8);
+
+        crate::impl_zero_heap!(Flags, Other);
+
+        assert!(!Flags::IS_DYNAMIC);
+        assert_eq!(Flags::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&Flags(0b101)), 0);
+        assert_eq!(data_size(&Other(1)), 0);
+        assert_eq!(
+            data_size(&vec![Flags(1); 4]),
+            4 * 4 + allocation_overhead(4 * 4)
+        );
+    }
+
+    #[test]
+    fn test_impl_forwarding() {
+        struct Ids(Vec<u32>);
+
+        #[allow(dead_code)]
+        struct Named<T> {
+            name: &'static str,
+            inner: T,
+        }
+
+        struct Pair<A, B>(A, B);
+
+        crate::impl_forwarding!(Ids => .0);
+        crate::impl_forwarding!(Named<T> => .inner);
+        crate::impl_forwarding!(Pair<A, B> => .1);
+
+        let ids = Ids(vec![1, 2, 3]);
+        assert!(Ids::IS_DYNAMIC);
+        assert_eq!(data_size(&ids), data_size(&ids.0));
+
+        let named = Named {
+            name: "boxed",
+            inner: Box::new(5u64),
+        };
+        assert!(!Named::<Box<u64>>::IS_DYNAMIC);
+        assert_eq!(
+            Named::<Box<u64>>::STATIC_HEAP_SIZE,
+            Box::<u64>::STATIC_HEAP_SIZE
+        );
+        assert_eq!(data_size(&named), data_size(&named.inner));
+
+        // Only the selected field is counted.
+        let pair = Pair(vec![0u8; 100], 1u8);
+        assert!(!Pair::<Vec<u8>, u8>::IS_DYNAMIC);
+        assert_eq!(data_size(&pair), 0);
+        assert_eq!(
+            crate::data_size_with_limit(
+                &Named {
+                    name: "",
+                    inner: ids
+                },
+                1
+            ),
+            (12 + allocation_overhead(12), false)
+        );
+    }
+
+    #[cfg(feature = "deref-impls")]
+    #[test]
+    fn test_impl_datasize_via_deref() {
+        use core::ops::Deref;
+
+        /// Dereferences to the box, which owns the heap data.
+        struct Owning(Box<u64>);
+
+        impl Deref for Owning {
+            type Target = Box<u64>;
+
+            fn deref(&self) -> &Box<u64> {
+                &self.0
+            }
+        }
+
+        /// Dereferences through the box to the value inside it.
+        struct Transparent(Box<u64>);
+
+        impl Deref for Transparent {
+            type Target = u64;
+
+            fn deref(&self) -> &u64 {
+                &self.0
+            }
+        }
+
+        crate::impl_datasize_via_deref!(Owning, Transparent);
+
+        assert_eq!(Owning::STATIC_HEAP_SIZE, 8 + allocation_overhead(8));
+        assert_eq!(data_size(&Owning(Box::new(1))), 8 + allocation_overhead(8));
+
+        // Only the target is sized, the box is not visible through it.
+        assert_eq!(Transparent::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&Transparent(Box::new(1))), 0);
+    }
+
+    #[test]
+    fn test_heapless_core_types() {
+        #[derive(DataSize)]
+        struct Comparison {
+            ordering: core::cmp::Ordering,
+            category: core::num::FpCategory,
+            counter: core::num::Wrapping<u32>,
+            total: core::num::Saturating<u64>,
+        }
+
+        let comparison = Comparison {
+            ordering: core::cmp::Ordering::Less,
+            category: 1.5f64.classify(),
+            counter: core::num::Wrapping(1),
+            total: core::num::Saturating(2),
+        };
+
+        assert!(!Comparison::IS_DYNAMIC);
+        assert_eq!(Comparison::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&comparison), 0);
+    }
+
+    #[test]
+    fn test_assert_static_heap_size() {
+        #[derive(DataSize)]
+        struct TwoBoxes {
+            a: Box<u32>,
+            b: Box<u64>,
+            items: Vec<u8>,
+        }
+
+        crate::assert_static_heap_size!(TwoBoxes, 12 + 2 * crate::allocation_overhead(1));
+        crate::assert_static_heap_size!([Box<u16>; 3], 3 * (2 + crate::allocation_overhead(2)));
+        crate::assert_static_heap_size!(u64, 0);
+    }
+
+    #[test]
+    #[allow(clippy::borrowed_box)]
+    fn test_owned_data_size() {
+        let boxed = Box::new(1u64);
+        let reference: &Box<u64> = &boxed;
+
+        // The reference itself owns nothing, the box it points to does.
+        assert_eq!(data_size(&reference), 0);
+        assert_eq!(
+            crate::owned_data_size(reference),
+            8 + crate::allocation_overhead(8)
+        );
+        assert_eq!(crate::owned_data_size(reference), data_size(reference));
+        assert_eq!(crate::owned_data_size(&reference), 0);
+
+        #[derive(DataSize)]
+        struct Borrowing<'a> {
+            value: &'a Box<u64>,
+        }
+
+        let borrowing = Borrowing { value: reference };
+        assert_eq!(data_size(&borrowing), 0);
+        assert_eq!(crate::owned_data_size(borrowing.value), data_size(&boxed));
+    }
+
+    #[test]
+    fn test_data_size_human() {
+        let data = vec![0u8; 1536];
+        assert_eq!(
+            crate::data_size_human(&data),
+            crate::Size(1536 + allocation_overhead(1536))
+        );
+        assert_eq!(crate::data_size_human(&data).to_string(), "1.5 KiB");
+    }
+
+    #[test]
+    fn test_data_size_of_iter() {
+        let items: [Option<u32>; 3] = [Some(1), None, Some(3)];
+        assert_eq!(crate::data_size_of_iter(&items), 0);
+
+        // Items filtered with an unknown count are still visited.
+        let values = [1u64, 2, 3, 4];
+        assert_eq!(
+            crate::data_size_of_iter(values.iter().filter(|v| **v > 2)),
+            0
+        );
+    }
+
     #[test]
     fn test_newtype_struct() {
         #[derive(DataSize)]
@@ -530,10 +1968,17 @@ mod tests {
         assert_eq!(Result::<Box<u8>, u16>::STATIC_HEAP_SIZE, 0);
         assert!(Result::<Box<u8>, u16>::IS_DYNAMIC);
 
-        assert_eq!(Result::<Box<u8>, Box<u16>>::STATIC_HEAP_SIZE, 1);
+        assert_eq!(
+            Result::<Box<u8>, Box<u16>>::STATIC_HEAP_SIZE,
+            1 + allocation_overhead(1)
+        );
         assert!(Result::<Box<u8>, Box<u16>>::IS_DYNAMIC);
 
-        assert_eq!(Result::<Box<u16>, Box<u16>>::STATIC_HEAP_SIZE, 2);
+        assert_eq!(
+            Result::<Box<u16>, Box<u16>>::STATIC_HEAP_SIZE,
+            2 + allocation_overhead(2)
+        );
+
         assert!(!Result::<Box<u16>, Box<u16>>::IS_DYNAMIC);
 
         assert_eq!(Result::<u16, Vec<u16>>::STATIC_HEAP_SIZE, 0);
@@ -546,6 +1991,437 @@ mod tests {
         assert!(Result::<Vec<u16>, Vec<u16>>::IS_DYNAMIC);
     }
 
+    /// A non-dynamic type that fails the test if it is ever visited individually.
+    struct NeverVisited;
+
+    impl DataSize for NeverVisited {
+        const IS_DYNAMIC: bool = false;
+        const STATIC_HEAP_SIZE: usize = 3;
+
+        fn estimate_heap_size(&self) -> usize {
+            panic!("non-dynamic element was visited")
+        }
+    }
+
+    #[test]
+    fn test_data_size_with_depth() {
+        /// A singly linked list, nesting one box per node.
+        struct Node {
+            #[allow(dead_code)]
+            value: u64,
+            next: Option<Box<Node>>,
+        }
+
+        // Deriving is not possible here, as `IS_DYNAMIC` of `Option<Box<Node>>` depends on itself.
+        impl DataSize for Node {
+            const IS_DYNAMIC: bool = true;
+            const STATIC_HEAP_SIZE: usize = 0;
+
+            fn estimate_heap_size(&self) -> usize {
+                self.next.estimate_heap_size()
+            }
+
+            fn estimate_heap_size_with_limit(&self, limit: &mut crate::Limit) -> usize {
+                self.next.estimate_heap_size_with_limit(limit)
+            }
+        }
+
+        let mut list = Node {
+            value: 0,
+            next: None,
+        };
+        for value in 1..1000 {
+            list = Node {
+                value,
+                next: Some(Box::new(list)),
+            };
+        }
+
+        let node_size = core::mem::size_of::<Node>() + crate::allocation_overhead(1);
+        assert_eq!(data_size(&list), 999 * node_size);
+        assert_eq!(
+            crate::data_size_with_depth(&list, 1000),
+            (999 * node_size, false)
+        );
+
+        // The allocations of the first 10 boxed nodes are visited, as well as the one they point to.
+        assert_eq!(
+            crate::data_size_with_depth(&list, 10),
+            (11 * node_size, true)
+        );
+        assert_eq!(crate::data_size_with_depth(&list, 0), (node_size, true));
+
+        // Elements of collections are nested one level below them, empty ones are not descended into.
+        let nested: Vec<Vec<Vec<u8>>> = vec![vec![vec![0; 8]], vec![]];
+        assert_eq!(
+            crate::data_size_with_depth(&nested, 2),
+            (data_size(&nested), false)
+        );
+        let innermost = 8 + allocation_overhead(8);
+        assert_eq!(
+            crate::data_size_with_depth(&nested, 1),
+            (data_size(&nested) - innermost, true)
+        );
+
+        // Boxed slices and trait objects nest their contents one level deeper, like boxed values.
+        let boxed: Box<[Vec<u8>]> = vec![vec![0; 8]].into_boxed_slice();
+        assert_eq!(
+            crate::data_size_with_depth(&boxed, 1),
+            (data_size(&boxed) - innermost, true)
+        );
+        let boxed: Box<dyn crate::DynDataSize> = Box::new(vec![vec![0u8; 8]]);
+        assert_eq!(
+            crate::data_size_with_depth(&boxed, 2),
+            (data_size(&boxed), false)
+        );
+        assert_eq!(
+            crate::data_size_with_depth(&boxed, 1),
+            (data_size(&boxed) - innermost, true)
+        );
+    }
+
+    #[test]
+    fn test_array_of_non_dynamic_does_not_iterate() {
+        let value: [NeverVisited; 1000] = core::array::from_fn(|_| NeverVisited);
+
+        assert_eq!(<[NeverVisited; 1000]>::STATIC_HEAP_SIZE, 3000);
+        assert_eq!(data_size(&value), 3000);
+        assert_eq!(crate::data_size_with_limit(&value, 0), (3000, false));
+    }
+
+    #[test]
+    fn test_slice() {
+        #[derive(DataSize)]
+        struct View<'a> {
+            bytes: &'a [u8],
+        }
+
+        let bytes = [1u8, 2, 3, 4];
+        let view = View { bytes: &bytes };
+
+        assert!(!View::IS_DYNAMIC);
+        assert_eq!(View::STATIC_HEAP_SIZE, 0);
+        assert_eq!(data_size(&view), 0);
+
+        // Bare slices are sized by the heap data of their elements.
+        assert!(!<[u8]>::IS_DYNAMIC);
+        assert_eq!(data_size::<[u8]>(&bytes), 0);
+        assert!(<[NeverVisited]>::IS_DYNAMIC);
+        assert_eq!(
+            data_size::<[NeverVisited]>(&[NeverVisited, NeverVisited]),
+            6
+        );
+    }
+
+    #[test]
+    fn test_boxed_slice_of_non_dynamic_does_not_iterate() {
+        let value: Box<[NeverVisited]> = (0..1000).map(|_| NeverVisited).collect();
+
+        // The elements are zero-sized, so the slice itself does not allocate.
+        assert_eq!(data_size(&value), 3000);
+        assert_eq!(crate::data_size_with_limit(&value, 0), (3000, false));
+
+        // Dynamic elements are visited one by one, and thus count against the limit.
+        let nested: Box<[Vec<u8>]> = vec![vec![0; 4], vec![0; 8]].into_boxed_slice();
+        let slots = 2 * core::mem::size_of::<Vec<u8>>();
+        let slots = slots + allocation_overhead(slots);
+        let (first, second) = (4 + allocation_overhead(4), 8 + allocation_overhead(8));
+        assert_eq!(data_size(&nested), slots + first + second);
+        assert_eq!(
+            crate::data_size_with_limit(&nested, 1),
+            (slots + first, true)
+        );
+    }
+
+    #[test]
+    fn test_byte_buffers_are_not_iterated() {
+        // Non-dynamic elements are sized by their count alone.
+        let value: Vec<NeverVisited> = (0..1000).map(|_| NeverVisited).collect();
+        assert_eq!(data_size(&value), 3000);
+        assert_eq!(crate::data_size_with_limit(&value, 0), (3000, false));
+
+        // Bytes take the same path, so the estimate does not depend on the contents.
+        assert!(!u8::IS_DYNAMIC);
+        let bytes = vec![0u8; 1 << 20];
+        let size = (1 << 20) + allocation_overhead(1 << 20);
+        assert_eq!(data_size(&bytes), size);
+        assert_eq!(crate::data_size_with_limit(&bytes, 0), (size, false));
+
+        // A `String` is sized exactly like its bytes.
+        let mut text = String::with_capacity(64);
+        text.push_str("hello");
+        let size = data_size(&text);
+        assert_eq!(size, data_size(&text.into_bytes()));
+    }
+
+    #[test]
+    fn test_static_heap_size_saturates() {
+        /// A type whose static heap size overflows when doubled.
+        struct Huge;
+
+        impl DataSize for Huge {
+            const IS_DYNAMIC: bool = false;
+            const STATIC_HEAP_SIZE: usize = usize::MAX / 2 + 1;
+
+            fn estimate_heap_size(&self) -> usize {
+                Self::STATIC_HEAP_SIZE
+            }
+        }
+
+        #[derive(DataSize)]
+        struct TwoHuge {
+            a: Huge,
+            b: Huge,
+        }
+
+        assert_eq!(<[Huge; 4]>::STATIC_HEAP_SIZE, usize::MAX);
+        assert_eq!(<(Huge, Huge, u8)>::STATIC_HEAP_SIZE, usize::MAX);
+        assert_eq!(TwoHuge::STATIC_HEAP_SIZE, usize::MAX);
+        assert_eq!(<[Huge; 1]>::STATIC_HEAP_SIZE, usize::MAX / 2 + 1);
+    }
+
+    #[test]
+    fn test_nested_vec_of_non_dynamic_does_not_iterate() {
+        // The outer `Vec` visits every inner one, which are sized without visiting their elements.
+        let value: Vec<Vec<NeverVisited>> = (0..10)
+            .map(|_| (0..100).map(|_| NeverVisited).collect())
+            .collect();
+
+        let outer = 10 * core::mem::size_of::<Vec<NeverVisited>>();
+        assert_eq!(
+            data_size(&value),
+            outer + allocation_overhead(outer) + 10 * 100 * 3
+        );
+    }
+
+    #[test]
+    fn test_empty_array() {
+        let value: [NeverVisited; 0] = [];
+
+        assert!(!<[NeverVisited; 0]>::IS_DYNAMIC);
+        assert_eq!(data_size(&value), 0);
+    }
+
+    #[test]
+    fn test_data_size_with_limit_on_static_array() {
+        let value = [(1u8, 2u16); 32];
+
+        assert_eq!(crate::data_size_with_limit(&value, 0), (0, false));
+    }
+
+    #[cfg(feature = "detailed")]
+    #[test]
+    fn test_merge_same_shape() {
+        use crate::MemUsageNode;
+        use std::collections::HashMap;
+
+        let node = |a, b, c| {
+            let mut inner = HashMap::new();
+            inner.insert("b", MemUsageNode::Size(b));
+            inner.insert("c", MemUsageNode::Size(c));
+
+            let mut outer = HashMap::new();
+            outer.insert("a", MemUsageNode::Size(a));
+            outer.insert("inner", MemUsageNode::Detailed(inner));
+            MemUsageNode::Detailed(outer)
+        };
+
+        let mut merged = node(1, 2, 3);
+        merged.merge(node(10, 20, 30));
+        assert_eq!(merged, node(11, 22, 33));
+
+        let mut size = MemUsageNode::Size(5);
+        size.merge(MemUsageNode::Size(7));
+        assert_eq!(size, MemUsageNode::Size(12));
+    }
+
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    #[test]
+    fn test_data_size_breakdown() {
+        #[derive(DataSize)]
+        struct Labels {
+            names: Vec<String>,
+            tag: String,
+        }
+
+        #[derive(DataSize)]
+        struct Document {
+            body: Vec<u8>,
+            title: String,
+            labels: Labels,
+        }
+
+        let document = Document {
+            body: vec![0; 1000],
+            title: "title".to_owned(),
+            labels: Labels {
+                names: vec!["draft".to_owned(), "review".to_owned()],
+                tag: "doc".to_owned(),
+            },
+        };
+
+        let breakdown = crate::data_size_breakdown(&document);
+        let paths: Vec<&str> = breakdown.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["body", "labels.names", "title", "labels.tag"]);
+        let names = 2 * core::mem::size_of::<String>();
+        assert_eq!(breakdown[0].1, 1000 + allocation_overhead(1000));
+        assert_eq!(
+            breakdown[1].1,
+            names
+                + allocation_overhead(names)
+                + 5
+                + allocation_overhead(5)
+                + 6
+                + allocation_overhead(6)
+        );
+        assert_eq!(
+            breakdown.iter().map(|(_, size)| size).sum::<usize>(),
+            data_size(&document)
+        );
+
+        assert_eq!(
+            crate::data_size_breakdown(&vec![0u8; 8]),
+            vec![(String::new(), 8 + allocation_overhead(8))]
+        );
+    }
+
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    #[test]
+    fn test_diff_growing_cache() {
+        use crate::{MemUsageDiff, MemUsageNode};
+        use std::collections::HashMap;
+
+        #[derive(DataSize)]
+        struct Cache {
+            entries: Vec<Vec<u8>>,
+            name: String,
+        }
+
+        #[derive(DataSize)]
+        struct Grown {
+            entries: Vec<Vec<u8>>,
+            name: String,
+            index: Vec<u32>,
+        }
+
+        let before = crate::data_size_detailed(&Cache {
+            entries: vec![vec![0; 16]],
+            name: "cache".to_owned(),
+        });
+        let after = crate::data_size_detailed(&Grown {
+            entries: vec![vec![0; 16], vec![0; 32]],
+            name: "c".to_owned(),
+            index: vec![1, 2],
+        });
+
+        let mut expected = HashMap::new();
+        expected.insert(
+            "entries",
+            MemUsageDiff::Changed(
+                (core::mem::size_of::<Vec<u8>>() + 32 + allocation_overhead(32)) as isize,
+            ),
+        );
+        expected.insert("name", MemUsageDiff::Changed(-4));
+        expected.insert("index", MemUsageDiff::Added(8 + allocation_overhead(8)));
+        let diff = before.diff(&after);
+        assert_eq!(diff, MemUsageDiff::Detailed(expected));
+        assert_eq!(
+            diff.total(),
+            after.total() as isize - before.total() as isize
+        );
+
+        // Reversing the order removes the added key again.
+        match after.diff(&before) {
+            MemUsageDiff::Detailed(members) => {
+                assert_eq!(
+                    members["index"],
+                    MemUsageDiff::Removed(8 + allocation_overhead(8))
+                )
+            }
+            other => panic!("expected detailed diff, got {:?}", other),
+        }
+
+        assert_eq!(
+            MemUsageNode::Size(10).diff(&MemUsageNode::Size(3)),
+            MemUsageDiff::Changed(-7)
+        );
+    }
+
+    #[cfg(feature = "detailed")]
+    #[test]
+    fn test_merge_mismatched() {
+        use crate::MemUsageNode;
+        use std::collections::HashMap;
+
+        let mut members = HashMap::new();
+        members.insert("a", MemUsageNode::Size(1));
+        members.insert("b", MemUsageNode::Size(2));
+
+        // Disjoint keys are kept.
+        let mut other_members = HashMap::new();
+        other_members.insert("c", MemUsageNode::Size(3));
+        let mut merged = MemUsageNode::Detailed(members.clone());
+        merged.merge(MemUsageNode::Detailed(other_members));
+        assert_eq!(merged.total(), 6);
+        if let MemUsageNode::Detailed(ref merged_members) = merged {
+            assert_eq!(merged_members.len(), 3);
+        } else {
+            panic!("expected detailed node");
+        }
+
+        // A size and a detailed node collapse into a size, regardless of order.
+        let mut size_first = MemUsageNode::Size(10);
+        size_first.merge(MemUsageNode::Detailed(members.clone()));
+        assert_eq!(size_first, MemUsageNode::Size(13));
+
+        let mut detailed_first = MemUsageNode::Detailed(members);
+        detailed_first.merge(MemUsageNode::Size(10));
+        assert_eq!(detailed_first, MemUsageNode::Size(13));
+    }
+
+    #[cfg(feature = "detailed")]
+    #[test]
+    fn test_prune() {
+        use crate::MemUsageNode;
+        use std::collections::HashMap;
+
+        let mut small = HashMap::new();
+        small.insert("x", MemUsageNode::Size(2));
+        small.insert("y", MemUsageNode::Size(3));
+
+        let mut nested_small = HashMap::new();
+        nested_small.insert("z", MemUsageNode::Size(1));
+
+        let mut large = HashMap::new();
+        large.insert("buffer", MemUsageNode::Size(100));
+        large.insert("nested", MemUsageNode::Detailed(nested_small));
+
+        let mut root = HashMap::new();
+        root.insert("small", MemUsageNode::Detailed(small));
+        root.insert("large", MemUsageNode::Detailed(large));
+        root.insert("leaf", MemUsageNode::Size(4));
+
+        let mut tree = MemUsageNode::Detailed(root);
+        let total = tree.total();
+        tree.prune(10);
+        assert_eq!(tree.total(), total);
+
+        // Small subtrees become leaves, even when nested in a large one.
+        let mut pruned_large = HashMap::new();
+        pruned_large.insert("buffer", MemUsageNode::Size(100));
+        pruned_large.insert("nested", MemUsageNode::Size(1));
+
+        let mut expected = HashMap::new();
+        expected.insert("small", MemUsageNode::Size(5));
+        expected.insert("large", MemUsageNode::Detailed(pruned_large));
+        expected.insert("leaf", MemUsageNode::Size(4));
+        assert_eq!(tree, MemUsageNode::Detailed(expected));
+
+        // A threshold above the total collapses the whole tree.
+        tree.prune(total + 1);
+        assert_eq!(tree, MemUsageNode::Size(total));
+    }
+
     #[test]
     fn test_empty_struct() {
         #[derive(DataSize)]
@@ -577,6 +2453,7 @@ mod tests {
 
     #[test]
     fn macro_does_not_panic_on_foreign_attributes() {
+        #[allow(dead_code)]
         #[derive(DataSize)]
         /// This docstring shows up as `#[doc = ""]`...
         struct Foo {
@@ -644,4 +2521,60 @@ mod tests {
         };
         assert_eq!(value.estimate_heap_size(), 1234);
     }
+
+    #[test]
+    fn test_fn_pointer_fields() {
+        fn double(x: u8) -> u8 {
+            x.wrapping_mul(2)
+        }
+
+        #[derive(DataSize)]
+        struct Transform {
+            apply: fn(u8) -> u8,
+            #[data_size(with = crate::fn_ptr_size)]
+            fallback: &'static dyn Fn(u8) -> u8,
+        }
+
+        assert!(!<fn(u8) -> u8 as DataSize>::IS_DYNAMIC);
+        assert_eq!(
+            <unsafe extern "C" fn(u8, u16) as DataSize>::STATIC_HEAP_SIZE,
+            0
+        );
+
+        let value = Transform {
+            apply: double,
+            fallback: &|x| x,
+        };
+        assert_eq!((value.apply)(3), 6);
+        assert_eq!((value.fallback)(3), 3);
+        assert_eq!(data_size(&value), 0);
+    }
+
+    #[test]
+    fn test_derive_with_callback() {
+        fn increment(x: u32) -> u32 {
+            x + 1
+        }
+
+        #[derive(DataSize)]
+        struct Callback {
+            name: &'static str,
+            call: fn(u32) -> u32,
+        }
+
+        assert!(!Callback::IS_DYNAMIC);
+        assert_eq!(Callback::STATIC_HEAP_SIZE, 0);
+
+        let value = Callback {
+            name: "increment",
+            call: increment,
+        };
+        assert_eq!(value.name, "increment");
+        assert_eq!((value.call)(41), 42);
+        assert_eq!(data_size(&value), 0);
+
+        // Pointers with many arguments are covered as well.
+        type Wide = fn(u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8) -> u8;
+        assert!(!<Wide as DataSize>::IS_DYNAMIC);
+    }
 }
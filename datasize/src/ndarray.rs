@@ -0,0 +1,118 @@
+use super::{allocation_overhead, is_dynamic, sum_with_limit, DataSize, Limit};
+use core::mem::size_of;
+
+use ndarray::{ArrayBase, Dimension, OwnedRepr, ViewRepr};
+
+// An owned `Array` keeps its elements in a single buffer. It is sized by the number of elements it
+// holds, which matches the buffer unless the array was sliced in place. The shape and strides are
+// stored inline. Heap data owned by the elements is counted on top, like the elements of a `Vec`.
+impl<A, D> DataSize for ArrayBase<OwnedRepr<A>, D>
+where
+    A: DataSize,
+    D: Dimension,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let sz_elements = if is_dynamic::<A>() {
+            self.iter().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * A::STATIC_HEAP_SIZE
+        };
+
+        storage_size(self) + sz_elements
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        let sz_elements = if is_dynamic::<A>() {
+            sum_with_limit(self.iter(), limit)
+        } else {
+            self.len() * A::STATIC_HEAP_SIZE
+        };
+
+        storage_size(self) + sz_elements
+    }
+}
+
+// Views borrow the elements of another array, which are counted there.
+impl<A, D> DataSize for ArrayBase<ViewRepr<&A>, D>
+where
+    D: Dimension,
+{
+    const IS_DYNAMIC: bool = false;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl<A, D> DataSize for ArrayBase<ViewRepr<&mut A>, D>
+where
+    D: Dimension,
+{
+    const IS_DYNAMIC: bool = false;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Returns the size of the element buffer of an owned array.
+#[inline]
+fn storage_size<A, D>(array: &ArrayBase<OwnedRepr<A>, D>) -> usize
+where
+    D: Dimension,
+{
+    let size = array.len() * size_of::<A>();
+    size + allocation_overhead(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::size_of;
+
+    use ndarray::{Array1, Array2};
+
+    use crate::{allocation_overhead, data_size};
+
+    #[test]
+    fn test_array2_of_floats() {
+        let array = Array2::<f64>::zeros((3, 4));
+        let elements = 12 * size_of::<f64>();
+        assert_eq!(data_size(&array), elements + allocation_overhead(elements));
+
+        // Views do not own their elements.
+        assert_eq!(data_size(&array.view()), 0);
+
+        let mut array = array;
+        assert_eq!(data_size(&array.view_mut()), 0);
+
+        assert_eq!(data_size(&Array2::<f32>::zeros((0, 4))), 0);
+    }
+
+    #[test]
+    fn test_array_of_dynamic_elements() {
+        let array = Array1::from(vec![vec![0u8; 16], vec![0u8; 32]]);
+
+        let elements = 2 * size_of::<Vec<u8>>();
+        assert_eq!(
+            data_size(&array),
+            elements
+                + allocation_overhead(elements)
+                + 16
+                + allocation_overhead(16)
+                + 32
+                + allocation_overhead(32)
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use super::DataSize;
+
+// Both types are transparent wrappers around a float, which never holds any heap data.
+
+impl<T> DataSize for ordered_float::OrderedFloat<T> {
+    const IS_DYNAMIC: bool = false;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T> DataSize for ordered_float::NotNan<T> {
+    const IS_DYNAMIC: bool = false;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::{NotNan, OrderedFloat};
+
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_ordered_float() {
+        #[derive(DataSize)]
+        struct Samples {
+            values: Vec<OrderedFloat<f64>>,
+            threshold: NotNan<f32>,
+        }
+
+        let samples = Samples {
+            values: vec![OrderedFloat(1.0), OrderedFloat(2.5), OrderedFloat(-3.0)],
+            threshold: NotNan::new(0.5).unwrap(),
+        };
+
+        assert!(!OrderedFloat::<f64>::IS_DYNAMIC);
+        assert!(!NotNan::<f32>::IS_DYNAMIC);
+        assert_eq!(data_size(&samples.threshold), 0);
+        assert_eq!(data_size(&samples), 3 * 8 + allocation_overhead(3 * 8));
+    }
+}
@@ -0,0 +1,123 @@
+use super::{allocation_overhead, is_dynamic, sum_with_limit, DataSize, Limit};
+use core::mem::size_of;
+
+use petgraph::{
+    graph::{Edge, Graph, IndexType, Node},
+    EdgeType,
+};
+
+// A `Graph` stores its nodes and edges in two vectors. Every node holds its weight and the indices
+// of its first outgoing and incoming edges, every edge its weight, its endpoints and the indices of
+// the next edges of both endpoints. All indices are stored inline, so sizing both vectors by their
+// capacity covers them. Weights are counted on top, like the elements of a `Vec`.
+impl<N, E, Ty, Ix> DataSize for Graph<N, E, Ty, Ix>
+where
+    N: DataSize,
+    E: DataSize,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let sz_nodes = if is_dynamic::<N>() {
+            self.node_weights().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.node_count() * N::STATIC_HEAP_SIZE
+        };
+
+        let sz_edges = if is_dynamic::<E>() {
+            self.edge_weights().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.edge_count() * E::STATIC_HEAP_SIZE
+        };
+
+        storage_size(self) + sz_nodes + sz_edges
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        let sz_nodes = if is_dynamic::<N>() {
+            sum_with_limit(self.node_weights(), limit)
+        } else {
+            self.node_count() * N::STATIC_HEAP_SIZE
+        };
+
+        let sz_edges = if is_dynamic::<E>() {
+            sum_with_limit(self.edge_weights(), limit)
+        } else {
+            self.edge_count() * E::STATIC_HEAP_SIZE
+        };
+
+        storage_size(self) + sz_nodes + sz_edges
+    }
+}
+
+/// Returns the size of the node and edge vectors of a `Graph`, including their index storage.
+#[inline]
+fn storage_size<N, E, Ty, Ix>(graph: &Graph<N, E, Ty, Ix>) -> usize
+where
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    let (node_capacity, edge_capacity) = graph.capacity();
+
+    let sz_nodes = node_capacity * size_of::<Node<N, Ix>>();
+    let sz_edges = edge_capacity * size_of::<Edge<E, Ix>>();
+
+    sz_nodes + allocation_overhead(sz_nodes) + sz_edges + allocation_overhead(sz_edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::size_of;
+
+    use petgraph::graph::{DiGraph, Edge, Node};
+
+    use crate::{allocation_overhead, data_size};
+
+    #[test]
+    fn test_graph_counts_storage() {
+        let mut graph: DiGraph<u32, u64> = DiGraph::with_capacity(4, 2);
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+        graph.add_edge(a, b, 3);
+
+        let (nodes, edges) = graph.capacity();
+        let node_size = size_of::<Node<u32>>();
+        let edge_size = size_of::<Edge<u64>>();
+
+        // Every node stores two edge indices, every edge two node and two edge indices.
+        assert_eq!(node_size, size_of::<u32>() + 2 * size_of::<u32>());
+        assert_eq!(edge_size, size_of::<u64>() + 4 * size_of::<u32>());
+        let (nodes, edges) = (nodes * node_size, edges * edge_size);
+        assert_eq!(
+            data_size(&graph),
+            nodes + allocation_overhead(nodes) + edges + allocation_overhead(edges)
+        );
+    }
+
+    #[test]
+    fn test_graph_counts_weights() {
+        let mut graph: DiGraph<Vec<u8>, String> = DiGraph::new();
+        let a = graph.add_node(vec![0; 100]);
+        let b = graph.add_node(vec![0; 50]);
+        graph.add_edge(a, b, String::from("a to b"));
+
+        let (nodes, edges) = graph.capacity();
+        let (nodes, edges) = (
+            nodes * size_of::<Node<Vec<u8>>>(),
+            edges * size_of::<Edge<String>>(),
+        );
+        let storage = nodes + allocation_overhead(nodes) + edges + allocation_overhead(edges);
+        let weights = 100 + allocation_overhead(100) + 50 + allocation_overhead(50) + 6;
+
+        assert_eq!(
+            data_size(&graph),
+            storage + weights + allocation_overhead(6)
+        );
+    }
+}
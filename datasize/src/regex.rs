@@ -0,0 +1,46 @@
+use super::DataSize;
+
+// The compiled program of a `Regex` is not exposed by the `regex` crate, so only the bytes of its
+// pattern, which is kept alongside it, are counted. This is a rough lower bound: the automata built
+// from the pattern and the caches used while matching are usually many times larger.
+impl DataSize for regex::Regex {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.as_str().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_regex() {
+        #[derive(DataSize)]
+        struct Filter {
+            patterns: Vec<Regex>,
+        }
+
+        let short = Regex::new("a+").unwrap();
+        let long = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})$").unwrap();
+
+        assert_eq!(data_size(&short), 2);
+        assert!(data_size(&long) > data_size(&short));
+
+        let filter = Filter {
+            patterns: vec![short.clone(), long.clone()],
+        };
+        let patterns = 2 * core::mem::size_of::<Regex>();
+        assert_eq!(
+            data_size(&filter),
+            patterns + allocation_overhead(patterns) + data_size(&short) + data_size(&long)
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use super::alloc::btree_size;
+use super::{DataSize, Limit};
+
+use serde_json::{Map, Value};
+
+// Numbers, booleans and `null` are stored inline. Note that numbers are assumed to not own any heap
+// data, which does not hold if the `arbitrary_precision` feature of `serde_json` is enabled.
+impl DataSize for Value {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        match self {
+            Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+            Value::String(string) => string.estimate_heap_size(),
+            Value::Array(array) => array.estimate_heap_size(),
+            Value::Object(object) => object.estimate_heap_size(),
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        match self {
+            Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+            Value::String(string) => string.estimate_heap_size(),
+            Value::Array(array) => array.estimate_heap_size_with_limit(limit),
+            Value::Object(object) => object.estimate_heap_size_with_limit(limit),
+        }
+    }
+}
+
+// A `Map` is backed by a `BTreeMap` (or an `IndexMap` with the `preserve_order` feature), so the
+// same approximation as for `BTreeMap` is used.
+impl DataSize for Map<String, Value> {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        btree_size::<String, Value>(self.len())
+            + self
+                .iter()
+                .map(|(key, value)| key.estimate_heap_size() + value.estimate_heap_size())
+                .sum::<usize>()
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        btree_size::<String, Value>(self.len())
+            + limit.descend(|limit| {
+                let mut size = 0;
+                for (key, value) in self.iter() {
+                    if !limit.consume_node() {
+                        break;
+                    }
+                    size += key.estimate_heap_size() + value.estimate_heap_size_with_limit(limit);
+                }
+                size
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::size_of;
+
+    use serde_json::{json, Value};
+
+    use crate::{allocation_overhead, data_size};
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(data_size(&Value::Null), 0);
+        assert_eq!(data_size(&json!(true)), 0);
+        assert_eq!(data_size(&json!(1.5)), 0);
+    }
+
+    #[test]
+    fn test_string_and_array() {
+        let string = Value::String("x".repeat(32));
+        assert_eq!(data_size(&string), 32 + allocation_overhead(32));
+
+        let array = Value::Array(vec![json!(1), Value::String("hello".to_owned())]);
+        let elements = 2 * size_of::<Value>();
+        assert_eq!(
+            data_size(&array),
+            elements + allocation_overhead(elements) + 5 + allocation_overhead(5)
+        );
+    }
+
+    #[test]
+    fn test_nested_object() {
+        let value = json!({
+            "name": "datasize",
+            "tags": ["heap", "memory"],
+            "version": 2,
+        });
+
+        assert!(
+            data_size(&value["tags"]) >= 2 * size_of::<Value>() + "heap".len() + "memory".len()
+        );
+
+        // Objects are sized like a `BTreeMap` of the same entries.
+        let entries: std::collections::BTreeMap<String, Value> = value
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        assert_eq!(data_size(&value), data_size(&entries));
+    }
+}
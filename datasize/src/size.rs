@@ -0,0 +1,96 @@
+//! Human readable heap sizes.
+
+use core::fmt;
+
+/// A number of bytes, displayed in human readable binary units.
+///
+/// Sizes of at least 1024 bytes are displayed in the largest unit they fill at least once, with
+/// one decimal place unless a different precision is requested, e.g. `1536` is displayed as
+/// `1.5 KiB`. Smaller sizes are displayed in bytes.
+///
+/// ```rust
+/// use datasize::Size;
+///
+/// assert_eq!(Size(512).to_string(), "512 B");
+/// assert_eq!(Size(1536).to_string(), "1.5 KiB");
+/// assert_eq!(format!("{:.2}", Size(3 * 1024 * 1024 / 4)), "768.00 KiB");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Size(pub usize);
+
+/// The binary units sizes are displayed in, following bytes.
+const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        let precision = f.precision().unwrap_or(1);
+        write!(f, "{:.*} {}", precision, value, UNITS[unit])
+    }
+}
+
+impl From<usize> for Size {
+    #[inline]
+    fn from(bytes: usize) -> Self {
+        Size(bytes)
+    }
+}
+
+impl From<Size> for usize {
+    #[inline]
+    fn from(size: Size) -> Self {
+        size.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Size;
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(Size(0).to_string(), "0 B");
+        assert_eq!(Size(1).to_string(), "1 B");
+        assert_eq!(Size(1023).to_string(), "1023 B");
+    }
+
+    #[test]
+    fn formats_kib() {
+        assert_eq!(Size(1024).to_string(), "1.0 KiB");
+        assert_eq!(Size(1536).to_string(), "1.5 KiB");
+        assert_eq!(Size(1024 * 1024 - 1024).to_string(), "1023.0 KiB");
+    }
+
+    #[test]
+    fn formats_mib_and_gib() {
+        assert_eq!(Size(1024 * 1024).to_string(), "1.0 MiB");
+        assert_eq!(Size(5 * 1024 * 1024 / 2).to_string(), "2.5 MiB");
+        assert_eq!(
+            Size(1024 * 1024 * 1024 - 1024 * 1024).to_string(),
+            "1023.0 MiB"
+        );
+        assert_eq!(Size(1024 * 1024 * 1024).to_string(), "1.0 GiB");
+    }
+
+    #[test]
+    fn formats_largest_unit() {
+        assert_eq!(Size(usize::MAX).to_string(), "16.0 EiB");
+    }
+
+    #[test]
+    fn respects_precision() {
+        assert_eq!(format!("{:.0}", Size(1536)), "2 KiB");
+        assert_eq!(format!("{:.3}", Size(1536)), "1.500 KiB");
+        assert_eq!(format!("{:.3}", Size(10)), "10 B");
+    }
+}
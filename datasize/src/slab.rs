@@ -0,0 +1,99 @@
+use super::{allocation_overhead, is_dynamic, sum_with_limit, DataSize, Limit};
+use core::mem::size_of;
+
+use slab::Slab;
+
+/// Mirrors the private entry type of `slab`, whose size is not exposed otherwise.
+#[allow(dead_code)]
+enum Entry<T> {
+    Vacant(usize),
+    Occupied(T),
+}
+
+// A `Slab` stores its entries in a single vector, where vacant slots hold the index of the next
+// vacant slot. Removing values does not shrink the vector, so vacant slots are still counted
+// through the `capacity`. Heap data of the values is counted on top, like the elements of a `Vec`.
+impl<T> DataSize for Slab<T>
+where
+    T: DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let sz_values = if is_dynamic::<T>() {
+            self.iter()
+                .map(|(_, value)| value.estimate_heap_size())
+                .sum()
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        };
+
+        storage_size(self) + sz_values
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        let sz_values = if is_dynamic::<T>() {
+            sum_with_limit(self.iter().map(|(_, value)| value), limit)
+        } else {
+            self.len() * T::STATIC_HEAP_SIZE
+        };
+
+        storage_size(self) + sz_values
+    }
+}
+
+/// Returns the size of the entry vector of a `Slab`, including vacant slots.
+#[inline]
+fn storage_size<T>(slab: &Slab<T>) -> usize {
+    let size = slab.capacity() * size_of::<Entry<T>>();
+    size + allocation_overhead(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use slab::Slab;
+
+    use super::Entry;
+    use crate::{allocation_overhead, data_size};
+
+    #[test]
+    fn test_slab_sized_by_capacity() {
+        let entry = core::mem::size_of::<Entry<u64>>();
+        let storage = 8 * entry + allocation_overhead(8 * entry);
+        assert_eq!(data_size(&Slab::<u64>::new()), 0);
+
+        let mut slab: Slab<u64> = Slab::with_capacity(8);
+        let keys: Vec<usize> = (0..8).map(|i| slab.insert(i)).collect();
+        assert_eq!(slab.capacity(), 8);
+        assert_eq!(data_size(&slab), storage);
+
+        // Vacant slots still occupy memory.
+        for key in &keys[..6] {
+            slab.remove(*key);
+        }
+        assert_eq!(slab.len(), 2);
+        assert_eq!(data_size(&slab), storage);
+    }
+
+    #[test]
+    fn test_slab_of_dynamic_values() {
+        let mut slab: Slab<Vec<u8>> = Slab::with_capacity(4);
+        let first = slab.insert(vec![0; 16]);
+        slab.insert(vec![0; 32]);
+
+        let storage = 4 * core::mem::size_of::<Entry<Vec<u8>>>();
+        let storage = storage + allocation_overhead(storage);
+        assert_eq!(
+            data_size(&slab),
+            storage + 16 + allocation_overhead(16) + 32 + allocation_overhead(32)
+        );
+
+        // Only occupied slots hold heap data.
+        slab.remove(first);
+        assert_eq!(data_size(&slab), storage + 32 + allocation_overhead(32));
+    }
+}
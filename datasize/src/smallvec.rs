@@ -1,4 +1,4 @@
-use super::DataSize;
+use super::{allocation_overhead, is_dynamic, sum_with_limit, DataSize, Limit};
 use core::mem::size_of;
 
 impl<A> DataSize for smallvec::SmallVec<A>
@@ -19,8 +19,9 @@ where
         // At this point, we're very similar to a regular `Vec`.
 
         let sz_base = self.capacity() * size_of::<A::Item>();
+        let sz_base = sz_base + allocation_overhead(sz_base);
 
-        let sz_used = if A::Item::IS_DYNAMIC {
+        let sz_used = if is_dynamic::<A::Item>() {
             self.iter().map(DataSize::estimate_heap_size).sum()
         } else {
             self.len() * A::Item::STATIC_HEAP_SIZE
@@ -28,4 +29,22 @@ where
 
         sz_base + sz_used
     }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if !self.spilled() {
+            return 0;
+        }
+
+        let sz_base = self.capacity() * size_of::<A::Item>();
+        let sz_base = sz_base + allocation_overhead(sz_base);
+
+        let sz_used = if is_dynamic::<A::Item>() {
+            sum_with_limit(self.iter(), limit)
+        } else {
+            self.len() * A::Item::STATIC_HEAP_SIZE
+        };
+
+        sz_base + sz_used
+    }
 }
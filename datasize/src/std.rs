@@ -1,8 +1,14 @@
-use super::{data_size, non_dynamic_const_heap_size, DataSize};
+//! Implementations for the types only available in `std`.
+
+use super::{
+    allocation_overhead, is_dynamic, non_dynamic_const_heap_size, sum_with_limit, DataSize, Limit,
+};
+#[cfg(feature = "detailed")]
+use super::{detailed_aggregate, detailed_buffer, MemUsageNode};
 
 use core::mem::size_of;
 
-use std::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use std::boxed::Box;
 
 non_dynamic_const_heap_size!(
   std::net::Ipv4Addr
@@ -17,41 +23,57 @@ non_dynamic_const_heap_size!(
   0
 );
 
-impl<T> DataSize for Box<T>
-where
-    T: DataSize,
-{
-    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+impl DataSize for Box<std::path::Path> {
+    const IS_DYNAMIC: bool = true;
 
-    const STATIC_HEAP_SIZE: usize = size_of::<T>();
+    const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        // Total size is the struct itself + its children.
-        size_of::<T>() + data_size::<T>(self)
+        let size = self.as_os_str().len();
+        size + allocation_overhead(size)
     }
 }
 
-impl<'a, T> DataSize for Cow<'a, T>
-where
-    T: 'a + ToOwned + ?Sized,
-    <T as ToOwned>::Owned: DataSize,
-{
+impl DataSize for Box<std::ffi::OsStr> {
     const IS_DYNAMIC: bool = true;
+
     const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        match self {
-            Cow::Borrowed(_) => 0,
-            Cow::Owned(inner) => inner.estimate_heap_size(),
-        }
+        self.len() + allocation_overhead(self.len())
+    }
+}
+
+impl DataSize for std::path::PathBuf {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.capacity() + allocation_overhead(self.capacity())
+    }
+}
+
+impl DataSize for std::ffi::OsString {
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        self.capacity() + allocation_overhead(self.capacity())
     }
 }
 
-// Please see the notes in the module docs on why Arcs are not counted.
-impl<T: ?Sized> DataSize for std::sync::Arc<T> {
+// The borrowed `Path` and `OsStr` types do not own any heap data. Note that a boxed path is sized by
+// the `Box` instead.
+
+impl DataSize for std::path::Path {
     const IS_DYNAMIC: bool = false;
+
     const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
@@ -60,8 +82,9 @@ impl<T: ?Sized> DataSize for std::sync::Arc<T> {
     }
 }
 
-impl<T: ?Sized> DataSize for std::rc::Rc<T> {
+impl DataSize for std::ffi::OsStr {
     const IS_DYNAMIC: bool = false;
+
     const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
@@ -70,135 +93,161 @@ impl<T: ?Sized> DataSize for std::rc::Rc<T> {
     }
 }
 
-// CONTAINERS
+// The buffer of a channel is shared between its handles and cannot be inspected, so messages still
+// in flight are not counted by either side.
 
-impl<T> DataSize for Vec<T>
-where
-    T: DataSize,
-{
-    const IS_DYNAMIC: bool = true;
+impl<T> DataSize for std::sync::mpsc::Sender<T> {
+    const IS_DYNAMIC: bool = false;
+    const STATIC_HEAP_SIZE: usize = 0;
 
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T> DataSize for std::sync::mpsc::SyncSender<T> {
+    const IS_DYNAMIC: bool = false;
     const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        // We do not include the `STATIC_HEAP_SIZE`, since the heap data has not been allocated yet.
-        let sz_base = self.capacity() * size_of::<T>();
+        0
+    }
+}
 
-        let sz_used = if T::IS_DYNAMIC {
-            self.iter().map(DataSize::estimate_heap_size).sum()
-        } else {
-            self.len() * T::STATIC_HEAP_SIZE
-        };
+impl<T> DataSize for std::sync::mpsc::Receiver<T> {
+    const IS_DYNAMIC: bool = false;
+    const STATIC_HEAP_SIZE: usize = 0;
 
-        sz_base + sz_used
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
     }
 }
 
-impl<T> DataSize for std::collections::VecDeque<T>
+impl<T> DataSize for std::io::Cursor<T>
 where
     T: DataSize,
 {
-    const IS_DYNAMIC: bool = true;
+    // A `Cursor` only adds a position to its inner value.
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
 
-    const STATIC_HEAP_SIZE: usize = 0;
+    const STATIC_HEAP_SIZE: usize = T::STATIC_HEAP_SIZE;
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        // We can treat a `VecDeque` exactly the same as a `Vec`.
-        let sz_base = self.capacity() * size_of::<T>();
-
-        let sz_used = if T::IS_DYNAMIC {
-            self.iter().map(DataSize::estimate_heap_size).sum()
-        } else {
-            self.len() * T::STATIC_HEAP_SIZE
-        };
+        self.get_ref().estimate_heap_size()
+    }
 
-        sz_base + sz_used
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.get_ref().estimate_heap_size_with_limit(limit)
     }
 }
 
-impl DataSize for String {
+// Buffered readers and writers own a buffer of fixed capacity on top of the wrapped value.
+
+impl<R> DataSize for std::io::BufReader<R>
+where
+    R: DataSize,
+{
     const IS_DYNAMIC: bool = true;
 
     const STATIC_HEAP_SIZE: usize = 0;
 
+    #[inline]
     fn estimate_heap_size(&self) -> usize {
-        self.capacity()
+        io_buffer_size(self.capacity()) + self.get_ref().estimate_heap_size()
     }
-}
-
-impl DataSize for std::path::PathBuf {
-    const IS_DYNAMIC: bool = true;
-
-    const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
-    fn estimate_heap_size(&self) -> usize {
-        self.capacity()
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        io_buffer_size(self.capacity()) + self.get_ref().estimate_heap_size_with_limit(limit)
     }
 }
 
-impl DataSize for std::ffi::OsString {
+impl<W> DataSize for std::io::BufWriter<W>
+where
+    W: DataSize + std::io::Write,
+{
     const IS_DYNAMIC: bool = true;
 
     const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        self.capacity()
+        io_buffer_size(self.capacity()) + self.get_ref().estimate_heap_size()
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        io_buffer_size(self.capacity()) + self.get_ref().estimate_heap_size_with_limit(limit)
     }
 }
 
-impl<K, V> DataSize for std::collections::BTreeMap<K, V>
+/// Returns the size of the buffer of a `BufReader` or `BufWriter` holding `capacity` bytes.
+#[inline]
+fn io_buffer_size(capacity: usize) -> usize {
+    capacity + allocation_overhead(capacity)
+}
+
+// Once cells may or may not hold a value, which changes their size at runtime.
+
+impl<T> DataSize for std::cell::OnceCell<T>
 where
-    K: DataSize,
-    V: DataSize,
+    T: DataSize,
 {
-    // Approximation directly taken from
-    // https://github.com/servo/heapsize/blob/f565dda63cc12c2a088bc9974a1b584cddec4382/src/lib.rs#L295-L306
-
     const IS_DYNAMIC: bool = true;
 
     const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        let mut size = 0;
+        self.get().map(DataSize::estimate_heap_size).unwrap_or(0)
+    }
 
-        if K::IS_DYNAMIC || V::IS_DYNAMIC {
-            for (key, value) in self.iter() {
-                size += size_of::<(K, V)>() + key.estimate_heap_size() + value.estimate_heap_size();
-            }
-        } else {
-            size += self.len() * (size_of::<(K, V)>() + K::STATIC_HEAP_SIZE + V::STATIC_HEAP_SIZE);
-        }
-        size
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.get()
+            .map(|value| value.estimate_heap_size_with_limit(limit))
+            .unwrap_or(0)
     }
 }
 
-impl<T> DataSize for std::collections::BTreeSet<T>
+impl<T> DataSize for std::sync::OnceLock<T>
 where
     T: DataSize,
 {
-    // A BTreeSet<T> is implemented as BTreeMap<T, ()> in the standard library, so we use the same
-    // estimate as above.
-
     const IS_DYNAMIC: bool = true;
 
     const STATIC_HEAP_SIZE: usize = 0;
 
     #[inline]
     fn estimate_heap_size(&self) -> usize {
-        if T::IS_DYNAMIC {
-            self.len() * size_of::<T>() + self.iter().map(T::estimate_heap_size).sum::<usize>()
-        } else {
-            self.len() * (size_of::<T>() + T::STATIC_HEAP_SIZE)
-        }
+        self.get().map(DataSize::estimate_heap_size).unwrap_or(0)
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        self.get()
+            .map(|value| value.estimate_heap_size_with_limit(limit))
+            .unwrap_or(0)
     }
 }
 
+/// Estimates the size of the table of a hashbrown based map holding up to `capacity` entries of `T`.
+///
+/// The table is sized by the `capacity` of the map, not its `len`: it grows to about 8/7 of the
+/// requested capacity, rounded up to a power of two buckets, each with an additional control byte.
+/// Removing entries leaves the table as large as it was, so a map that had many deletions is still
+/// sized by its peak capacity.
 fn estimate_hashbrown_rawtable<T>(capacity: usize) -> usize {
+    // A map without capacity has not allocated its table yet.
+    if capacity == 0 {
+        return 0;
+    }
+
     // https://github.com/rust-lang/hashbrown/blob/v0.12.3/src/raw/mod.rs#L185
     let buckets = if capacity < 8 {
         if capacity < 4 {
@@ -216,92 +265,1376 @@ fn estimate_hashbrown_rawtable<T>(capacity: usize) -> usize {
     // (through ctrl_align and Group::WIDTH, at most 31 bytes).
     let ctrl_offset = size * buckets;
     // Add one byte of "control" metadata per bucket
-    ctrl_offset + buckets
+    let table = ctrl_offset + buckets;
+    table + allocation_overhead(table)
+}
+
+/// The heap size of the `BuildHasher` state of a `HashMap` or `HashSet`.
+///
+/// Implemented for every type by default, reporting no heap size at all, as hashers usually do not
+/// hold any heap data. With the `count-hasher-state` feature enabled, it is implemented for types
+/// implementing `DataSize` only and reports their heap size instead.
+pub trait HasherHeapSize {
+    /// Returns the heap size of the hasher state.
+    fn hasher_heap_size(&self) -> usize;
+}
+
+#[cfg(not(feature = "count-hasher-state"))]
+impl<S> HasherHeapSize for S {
+    #[inline]
+    fn hasher_heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(feature = "count-hasher-state")]
+impl<S: DataSize> HasherHeapSize for S {
+    #[inline]
+    fn hasher_heap_size(&self) -> usize {
+        self.estimate_heap_size()
+    }
+}
+
+non_dynamic_const_heap_size!(std::collections::hash_map::RandomState, 0);
+
+impl<H> DataSize for std::hash::BuildHasherDefault<H> {
+    const IS_DYNAMIC: bool = false;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        0
+    }
 }
 
+// The hasher `S` is only bounded by `HasherHeapSize`, which supports any `BuildHasher` unless the
+// `count-hasher-state` feature is enabled.
 impl<K, V, S> DataSize for std::collections::HashMap<K, V, S>
 where
     K: DataSize,
     V: DataSize,
+    S: HasherHeapSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        let size = estimate_hashbrown_rawtable::<(K, V)>(self.capacity())
+            + self.hasher().hasher_heap_size();
+
+        // Keys and values are sized separately, so only the dynamic side is iterated.
+        let sz_keys = if is_dynamic::<K>() {
+            self.keys().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * K::STATIC_HEAP_SIZE
+        };
+
+        let sz_values = if is_dynamic::<V>() {
+            self.values().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * V::STATIC_HEAP_SIZE
+        };
+
+        size + sz_keys + sz_values
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if !(is_dynamic::<K>() || is_dynamic::<V>()) {
+            return self.estimate_heap_size();
+        }
+
+        let mut size = estimate_hashbrown_rawtable::<(K, V)>(self.capacity())
+            + self.hasher().hasher_heap_size();
+        if !self.is_empty() {
+            size += limit.descend(|limit| {
+                let mut size = 0;
+                for (key, value) in self.iter() {
+                    if !limit.consume_node() {
+                        break;
+                    }
+                    size += key.estimate_heap_size_with_limit(limit)
+                        + value.estimate_heap_size_with_limit(limit);
+                }
+                size
+            });
+        }
+        size
+    }
+
+    // Unlike other collections, maps always separate their table from the heap data of the
+    // entries, as their table overhead is often significant.
+    #[cfg(feature = "detailed")]
+    #[inline]
+    fn estimate_detailed_heap_size(&self) -> MemUsageNode {
+        let table = estimate_hashbrown_rawtable::<(K, V)>(self.capacity())
+            + self.hasher().hasher_heap_size();
+
+        let entries = match (
+            detailed_aggregate(self.keys()),
+            detailed_aggregate(self.values()),
+        ) {
+            (MemUsageNode::Size(keys), MemUsageNode::Size(values)) => {
+                MemUsageNode::Size(keys + values)
+            }
+            (keys, values) => {
+                let mut members = std::collections::HashMap::new();
+                members.insert("keys", keys);
+                members.insert("values", values);
+                MemUsageNode::Detailed(members)
+            }
+        };
+
+        if cfg!(feature = "detailed-spare") {
+            // The table is split into the slots holding entries and everything else.
+            return detailed_buffer(
+                "table",
+                table,
+                self.len() * size_of::<(K, V)>(),
+                vec![("entries", entries)],
+            );
+        }
+
+        let mut members = std::collections::HashMap::new();
+        members.insert("table", MemUsageNode::Size(table));
+        members.insert("entries", entries);
+        MemUsageNode::Detailed(members)
+    }
+}
+
+/// Approximates the heap size of a `HashMap` by sizing only a sample of its entries.
+///
+/// The table of the map is always counted in full, but only up to `sample` entries are sized
+/// individually. Their average heap size is then extrapolated to all `len()` entries of the map.
+///
+/// The result is an approximation only and can be arbitrarily far off if the sizes of the entries
+/// vary a lot, but the cost of estimating no longer grows with the size of the map. Like most
+/// functions used with `#[data_size(with = ...)]`, it needs a small wrapper fixing the sample size:
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use datasize::DataSize;
+///
+/// fn sample_100(map: &HashMap<u64, Vec<u8>>) -> usize {
+///     datasize::estimate_map_sampled(map, 100)
+/// }
+///
+/// #[derive(DataSize)]
+/// struct Cache {
+///     #[data_size(with = sample_100)]
+///     entries: HashMap<u64, Vec<u8>>,
+/// }
+/// ```
+pub fn estimate_map_sampled<K, V, S>(
+    map: &std::collections::HashMap<K, V, S>,
+    sample: usize,
+) -> usize
+where
+    K: DataSize,
+    V: DataSize,
+    S: HasherHeapSize,
+{
+    if !(is_dynamic::<K>() || is_dynamic::<V>()) {
+        return map.estimate_heap_size();
+    }
+
+    let table =
+        estimate_hashbrown_rawtable::<(K, V)>(map.capacity()) + map.hasher().hasher_heap_size();
+    let (sampled, sampled_size) = map
+        .iter()
+        .take(sample)
+        .fold((0, 0), |(count, size), (k, v)| {
+            (
+                count + 1,
+                size + k.estimate_heap_size() + v.estimate_heap_size(),
+            )
+        });
+
+    // An empty sample (or map) leaves only the table to count.
+    if sampled == 0 {
+        return table;
+    }
+
+    // Divide before multiplying, so that large maps of large values saturate instead of overflowing.
+    let average = sampled_size / sampled;
+    let remainder = sampled_size % sampled;
+    table.saturating_add(
+        average
+            .saturating_mul(map.len())
+            .saturating_add(remainder.saturating_mul(map.len()) / sampled),
+    )
+}
+
+impl<T, S> DataSize for std::collections::HashSet<T, S>
+where
+    T: DataSize,
+    S: HasherHeapSize,
 {
     const IS_DYNAMIC: bool = true;
 
-    const STATIC_HEAP_SIZE: usize = 0;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // HashSet<T> is based on HashMap<T, ()>
+        let size = estimate_hashbrown_rawtable::<(T, ())>(self.capacity())
+            + self.hasher().hasher_heap_size();
+
+        if is_dynamic::<T>() {
+            size + self.iter().map(T::estimate_heap_size).sum::<usize>()
+        } else {
+            size + self.len() * T::STATIC_HEAP_SIZE
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if is_dynamic::<T>() {
+            estimate_hashbrown_rawtable::<(T, ())>(self.capacity())
+                + self.hasher().hasher_heap_size()
+                + sum_with_limit(self, limit)
+        } else {
+            self.estimate_heap_size()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+    use core::mem::size_of;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_box() {
+        let value: Box<u64> = Box::new(1234);
+
+        assert_eq!(data_size::<Box<u64>>(&value), 8 + allocation_overhead(8));
+        assert_eq!(data_size(&value), 8 + allocation_overhead(8));
+    }
+
+    #[test]
+    fn test_estimate_map_sampled() {
+        use std::collections::HashMap;
+
+        let map: HashMap<u32, Vec<u8>> = (0..1000)
+            .map(|i| (i, vec![0; 100 + i as usize % 10]))
+            .collect();
+        let exact = data_size(&map);
+
+        let sampled = crate::estimate_map_sampled(&map, 50);
+        assert!(
+            sampled.abs_diff(exact) < exact / 50,
+            "{} vs {}",
+            sampled,
+            exact
+        );
+
+        // Sampling all entries is exact, an empty sample only counts the table.
+        assert_eq!(crate::estimate_map_sampled(&map, usize::MAX), exact);
+        assert!(crate::estimate_map_sampled(&map, 0) < 1000 * 100);
+        assert_eq!(
+            crate::estimate_map_sampled(&HashMap::<u32, Vec<u8>>::new(), 10),
+            data_size(&HashMap::<u32, Vec<u8>>::new())
+        );
+    }
+
+    #[test]
+    fn test_estimate_map_sampled_saturates() {
+        use std::collections::HashMap;
+
+        struct Huge;
+
+        impl DataSize for Huge {
+            const IS_DYNAMIC: bool = true;
+            const STATIC_HEAP_SIZE: usize = 0;
+
+            fn estimate_heap_size(&self) -> usize {
+                usize::MAX / 2
+            }
+        }
+
+        let map: HashMap<u32, Huge> = (0..4).map(|i| (i, Huge)).collect();
+        assert_eq!(crate::estimate_map_sampled(&map, 2), usize::MAX);
+    }
+
+    #[test]
+    fn test_box_alloc_overhead() {
+        let value: Box<u8> = Box::new(1);
+
+        if cfg!(feature = "alloc-overhead") {
+            assert_eq!(data_size(&value), 1 + crate::ALLOCATION_OVERHEAD);
+        } else {
+            assert_eq!(data_size(&value), 1);
+        }
+
+        // Empty allocations do not allocate, thus have no overhead either.
+        assert_eq!(data_size(&Vec::<u8>::new()), 0);
+        assert_eq!(data_size(&String::new()), 0);
+    }
+
+    #[test]
+    fn test_boxed_slice() {
+        let bytes: Box<[u8]> = vec![0u8; 10].into_boxed_slice();
+        assert!(Box::<[u8]>::IS_DYNAMIC);
+        assert_eq!(data_size(&bytes), 10 + allocation_overhead(10));
+
+        let nested: Box<[Vec<u8>]> = vec![vec![0; 4], vec![0; 8]].into_boxed_slice();
+        let slots = 2 * size_of::<Vec<u8>>();
+        assert_eq!(
+            data_size(&nested),
+            slots
+                + allocation_overhead(slots)
+                + 4
+                + allocation_overhead(4)
+                + 8
+                + allocation_overhead(8)
+        );
+
+        let empty: Box<[u64]> = Vec::new().into_boxed_slice();
+        assert_eq!(data_size(&empty), 0);
+
+        // Sized boxes keep their static heap size.
+        assert!(!Box::<u64>::IS_DYNAMIC);
+        assert_eq!(Box::<u64>::STATIC_HEAP_SIZE, 8 + allocation_overhead(8));
+        assert_eq!(data_size(&Box::new(1u64)), 8 + allocation_overhead(8));
+    }
+
+    #[test]
+    fn test_boxed_str() {
+        let value: Box<str> = String::from("hello").into_boxed_str();
+
+        assert_eq!(data_size(&value), 5 + allocation_overhead(5));
+    }
+
+    #[test]
+    fn test_boxed_any() {
+        use core::any::Any;
+
+        let value: Box<dyn Any> = Box::new([0u8; 64]);
+        let sendable: Box<dyn Any + Send + Sync> = Box::new(42u32);
+
+        assert_eq!(data_size(&value), 64 + allocation_overhead(64));
+        assert_eq!(data_size(&sendable), 4 + allocation_overhead(4));
+
+        // The heap data of the boxed value itself is not visible.
+        let hidden: Box<dyn Any + Send> = Box::new(vec![0u8; 128]);
+        let slot = size_of::<Vec<u8>>();
+        assert_eq!(data_size(&hidden), slot + allocation_overhead(slot));
+    }
+
+    #[test]
+    fn test_boxed_dyn_data_size() {
+        use crate::DynDataSize;
+
+        let values: Vec<Box<dyn DynDataSize>> = vec![
+            Box::new(7u32),
+            Box::new("hello".to_owned()),
+            Box::new(vec![0u64; 4]),
+            Box::new(Some(Box::new(1u8))),
+        ];
+
+        /// The size of a boxed value of `T` owning `heap` bytes in a single allocation.
+        fn boxed<T>(heap: usize) -> usize {
+            size_of::<T>() + allocation_overhead(size_of::<T>()) + heap + allocation_overhead(heap)
+        }
+
+        assert_eq!(data_size(&values[0]), boxed::<u32>(0));
+        assert_eq!(data_size(&values[1]), boxed::<String>(5));
+        assert_eq!(data_size(&values[2]), boxed::<Vec<u64>>(32));
+        assert_eq!(data_size(&values[3]), boxed::<Option<Box<u8>>>(1));
+
+        let items: usize = values.iter().map(data_size).sum();
+        let slots = 4 * size_of::<Box<dyn DynDataSize>>();
+        assert_eq!(
+            data_size(&values),
+            slots + allocation_overhead(slots) + items
+        );
+
+        let sendable: Box<dyn DynDataSize + Send + Sync> = Box::new(vec![0u8; 8]);
+        assert_eq!(data_size(&sendable), boxed::<Vec<u8>>(8));
+        assert_eq!(
+            crate::data_size_with_limit(&sendable, 10),
+            (boxed::<Vec<u8>>(8), false)
+        );
+    }
+
+    #[test]
+    fn test_boxed_path() {
+        let path: Box<std::path::Path> = std::path::PathBuf::from("/usr/bin").into_boxed_path();
+        let os_str: Box<std::ffi::OsStr> = std::ffi::OsString::from("hello").into_boxed_os_str();
+
+        assert_eq!(data_size(&path), 8 + allocation_overhead(8));
+        assert_eq!(data_size(&os_str), 5 + allocation_overhead(5));
+    }
+
+    #[test]
+    fn test_option_box() {
+        let value_none: Option<Box<u64>> = None;
+        let value_some: Option<Box<u64>> = Some(Box::new(12345));
+
+        assert_eq!(data_size::<Option<Box<u64>>>(&value_none), 0);
+        assert_eq!(
+            data_size::<Option<Box<u64>>>(&value_some),
+            8 + allocation_overhead(8)
+        );
+    }
+
+    #[test]
+    fn test_cow() {
+        let value: Cow<'static, str> = Cow::from("hello");
+        assert_eq!(data_size(&value), 0);
+
+        let value_owned: Cow<'static, str> = Cow::from("hello".to_owned());
+
+        assert_eq!(data_size(&value_owned), data_size(&"hello".to_owned()));
+    }
+
+    #[test]
+    #[cfg(feature = "allocator_api")]
+    fn test_vec_with_custom_allocator() {
+        use core::alloc::{AllocError, Allocator, Layout};
+        use core::cell::Cell;
+        use core::ptr::NonNull;
+
+        /// An allocator counting its allocations, forwarding to the system allocator.
+        #[derive(Default)]
+        struct Counting {
+            allocations: Cell<usize>,
+        }
+
+        unsafe impl Allocator for &Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.allocations.set(self.allocations.get() + 1);
+                std::alloc::System.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                unsafe { std::alloc::System.deallocate(ptr, layout) }
+            }
+        }
+
+        let allocator = Counting::default();
+        let mut values: Vec<Vec<u8>, &Counting> = Vec::with_capacity_in(2, &allocator);
+        values.push(vec![0; 10]);
+        values.push(vec![0; 20]);
+
+        // The allocator does not contribute, only the logical data is counted.
+        assert_eq!(allocator.allocations.get(), 1);
+        let slots = 2 * size_of::<Vec<u8>>();
+        assert_eq!(
+            data_size(&values),
+            slots
+                + allocation_overhead(slots)
+                + 10
+                + allocation_overhead(10)
+                + 20
+                + allocation_overhead(20)
+        );
+    }
+
+    #[test]
+    fn test_btree_map_node_estimate() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        // A node holds up to 11 entries, a parent pointer and two indices. Internal nodes have 12
+        // additional child pointers.
+        let leaf = 8 + 2 * 2 + 11 * 16;
+        let internal = leaf + 12 * 8;
+
+        for len in [10usize, 100, 10000] {
+            let map: BTreeMap<u64, u64> = (0..len as u64).map(|i| (i, i)).collect();
+            let size = data_size(&map);
+
+            // Nodes are at least half full (5 entries), and at most completely full.
+            assert!(size >= len.div_ceil(11) * leaf, "{}: {}", len, size);
+            assert!(size <= len.div_ceil(5) * internal, "{}: {}", len, size);
+        }
+
+        // Growth is roughly linear, at about 8 entries per node.
+        let size_of_len =
+            |len: u64| data_size(&(0..len).map(|i| (i, i)).collect::<BTreeMap<_, _>>());
+        let per_entry = (size_of_len(10000) - size_of_len(100)) / 9900;
+        assert!(
+            (leaf / 11..=internal / 5).contains(&per_entry),
+            "{}",
+            per_entry
+        );
+
+        let set: BTreeSet<u64> = (0..100).collect();
+        assert!(data_size(&set) < size_of_len(100));
+        assert_eq!(data_size(&BTreeMap::<u64, u64>::new()), 0);
+    }
+
+    #[test]
+    fn test_cow_slice() {
+        let data = [1u64, 2, 3];
+
+        let borrowed: Cow<[u64]> = Cow::Borrowed(&data);
+        assert_eq!(data_size(&borrowed), 0);
+
+        let owned: Cow<[u64]> = Cow::Owned(data.to_vec());
+        assert_eq!(data_size(&owned), 24 + allocation_overhead(24));
+
+        // Heap data of the elements is counted for the owned variant only.
+        let nested = [vec![0u8; 4], vec![0u8; 8]];
+        let borrowed: Cow<[Vec<u8>]> = Cow::Borrowed(&nested);
+        assert_eq!(data_size(&borrowed), 0);
+        let owned: Cow<[Vec<u8>]> = Cow::Owned(nested.to_vec());
+        assert_eq!(data_size(&owned), data_size(&nested.to_vec()));
+    }
+
+    #[test]
+    fn test_cow_custom_type() {
+        #[derive(Clone, DataSize)]
+        struct Config {
+            name: String,
+            values: Vec<u32>,
+        }
+
+        #[derive(DataSize)]
+        struct Layered<'a> {
+            config: Cow<'a, Config>,
+        }
+
+        let base = Config {
+            name: "base".to_owned(),
+            values: vec![1, 2, 3],
+        };
+
+        // The borrowed config is owned elsewhere.
+        let borrowed = Layered {
+            config: Cow::Borrowed(&base),
+        };
+        assert_eq!(data_size(&borrowed), 0);
+
+        // Once modified, the clone is owned by the `Cow` and counted.
+        let mut owned = Layered {
+            config: Cow::Borrowed(&base),
+        };
+        owned.config.to_mut().values.push(4);
+        assert!(matches!(owned.config, Cow::Owned(_)));
+        assert_eq!(data_size(&owned), data_size(&*owned.config));
+        assert!(data_size(&owned) >= 4 + 4 * 4);
+    }
+
+    #[test]
+    fn test_string() {
+        let value = "abcdef".to_string();
+
+        assert_eq!(data_size(&value), 6 + allocation_overhead(6));
+    }
+
+    #[test]
+    #[cfg(not(feature = "vec-len-sizing"))]
+    fn test_vec_counts_capacity() {
+        let mut value: Vec<u64> = Vec::with_capacity(10);
+        value.extend([1, 2, 3]);
+
+        assert_eq!(value.len(), 3);
+        assert_eq!(data_size(&value), 10 * 8 + allocation_overhead(10 * 8));
+    }
+
+    #[test]
+    #[cfg(not(feature = "vec-len-sizing"))]
+    fn test_vec_of_dynamic_counts_capacity_and_elements() {
+        let mut value: Vec<Vec<u8>> = Vec::with_capacity(4);
+        value.push(vec![0; 16]);
+
+        let slots = 4 * core::mem::size_of::<Vec<u8>>();
+        assert_eq!(
+            data_size(&value),
+            slots + allocation_overhead(slots) + data_size(&value[0])
+        );
+    }
+
+    #[test]
+    fn test_data_size_of_iter() {
+        let vecs: Vec<Vec<u8>> = vec![vec![0; 8], vec![0; 16], vec![]];
+
+        let (first, second) = (8 + allocation_overhead(8), 16 + allocation_overhead(16));
+        assert_eq!(crate::data_size_of_iter(&vecs), first + second);
+        assert_eq!(crate::data_size_of_iter(&vecs[..2]), first + second);
+        assert_eq!(crate::data_size_of_iter(vecs.iter().skip(1)), second);
+
+        // Boxes are not dynamic, so their count suffices.
+        let boxes: Vec<Box<u32>> = (0..5).map(Box::new).collect();
+        let boxed = 4 + allocation_overhead(4);
+        assert_eq!(crate::data_size_of_iter(&boxes), 5 * boxed);
+        assert_eq!(
+            crate::data_size_of_iter(boxes.iter().filter(|b| ***b > 1)),
+            3 * boxed
+        );
+    }
+
+    #[test]
+    fn test_channel_handles() {
+        #[derive(DataSize)]
+        struct Worker {
+            results: std::sync::mpsc::Sender<u8>,
+            commands: std::sync::mpsc::Receiver<Vec<u8>>,
+        }
+
+        let (results, _) = std::sync::mpsc::channel();
+        let (sender, commands) = std::sync::mpsc::channel();
+        sender.send(vec![0; 64]).unwrap();
+
+        // The pending message is not counted.
+        let worker = Worker { results, commands };
+        assert!(!Worker::IS_DYNAMIC);
+        assert_eq!(data_size(&worker), 0);
+    }
+
+    #[test]
+    fn test_binary_heap() {
+        let mut plain: std::collections::BinaryHeap<u32> = std::collections::BinaryHeap::new();
+        plain.reserve_exact(8);
+        plain.extend([3, 1, 2]);
+        // Sized like a `Vec`, by its length with `vec-len-sizing`.
+        fn buffer_len<T>(heap: &std::collections::BinaryHeap<T>) -> usize {
+            if cfg!(feature = "vec-len-sizing") {
+                heap.len()
+            } else {
+                heap.capacity()
+            }
+        }
+        let overhead = allocation_overhead(plain.capacity() * 4);
+        assert_eq!(data_size(&plain), buffer_len(&plain) * 4 + overhead);
+
+        let mut nested: std::collections::BinaryHeap<Vec<u8>> = std::collections::BinaryHeap::new();
+        nested.push(vec![0; 16]);
+        nested.push(vec![1; 32]);
+
+        let overhead = allocation_overhead(nested.capacity() * size_of::<Vec<u8>>());
+        assert_eq!(
+            data_size(&nested),
+            buffer_len(&nested) * size_of::<Vec<u8>>()
+                + overhead
+                + 16
+                + allocation_overhead(16)
+                + 32
+                + allocation_overhead(32)
+        );
+    }
+
+    #[test]
+    fn test_vec_of_string_pairs() {
+        // Environment variables are commonly collected as key/value pairs.
+        let vars: Vec<(String, String)> = [("HOME", "/root"), ("LANG", "C.UTF-8"), ("EMPTY", "")]
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        let strings: usize = vars
+            .iter()
+            .map(|(key, value)| {
+                key.capacity()
+                    + allocation_overhead(key.capacity())
+                    + value.capacity()
+                    + allocation_overhead(value.capacity())
+            })
+            .sum();
+        let slots = vars.capacity() * size_of::<(String, String)>();
+        assert!(<(String, String)>::IS_DYNAMIC);
+        assert_eq!(
+            data_size(&vars[0]),
+            4 + allocation_overhead(4) + 5 + allocation_overhead(5)
+        );
+        assert_eq!(
+            data_size(&vars),
+            slots + allocation_overhead(slots) + strings
+        );
+
+        #[derive(DataSize)]
+        struct Environment {
+            vars: Vec<(String, String)>,
+        }
+
+        let env = Environment {
+            vars: std::env::vars().collect(),
+        };
+        assert!(data_size(&env) >= env.vars.len() * size_of::<(String, String)>());
+    }
+
+    #[test]
+    #[cfg(not(feature = "vec-len-sizing"))]
+    fn test_vec_deque_and_string_count_capacity() {
+        let mut deque: std::collections::VecDeque<u32> = std::collections::VecDeque::new();
+        deque.reserve_exact(10);
+        deque.push_back(1);
+        assert_eq!(
+            data_size(&deque),
+            deque.capacity() * 4 + allocation_overhead(deque.capacity() * 4)
+        );
+
+        let mut string = String::with_capacity(32);
+        string.push_str("abc");
+        assert_eq!(data_size(&string), 32 + allocation_overhead(32));
+    }
+
+    #[test]
+    #[cfg(feature = "vec-len-sizing")]
+    fn test_vec_len_sizing_ignores_capacity() {
+        let mut value: Vec<u64> = Vec::with_capacity(10);
+        value.extend([1, 2, 3]);
+        // The allocation is still made according to the capacity.
+        assert_eq!(data_size(&value), 3 * 8 + allocation_overhead(10 * 8));
+
+        let mut deque: std::collections::VecDeque<u32> = std::collections::VecDeque::new();
+        deque.reserve_exact(10);
+        deque.push_back(1);
+        assert_eq!(
+            data_size(&deque),
+            4 + allocation_overhead(deque.capacity() * 4)
+        );
+
+        let mut string = String::with_capacity(32);
+        string.push_str("abc");
+        assert_eq!(data_size(&string), 3 + allocation_overhead(32));
+    }
+
+    #[test]
+    fn test_large_array_of_boxes() {
+        let value: [Box<u64>; 1000] = core::array::from_fn(|_| Box::new(0));
+
+        let size = 1000 * (8 + allocation_overhead(8));
+        assert!(!<[Box<u64>; 1000]>::IS_DYNAMIC);
+        assert_eq!(<[Box<u64>; 1000]>::STATIC_HEAP_SIZE, size);
+        assert_eq!(data_size(&value), size);
+    }
+
+    #[test]
+    fn test_array_of_vecs() {
+        let value: [Vec<u8>; 4] = [vec![0; 1], vec![0; 2], vec![0; 3], vec![0; 4]];
+
+        assert!(<[Vec<u8>; 4]>::IS_DYNAMIC);
+        assert_eq!(
+            data_size(&value),
+            1 + 2 + 3 + 4 + 4 * allocation_overhead(1)
+        );
+    }
+
+    #[test]
+    fn test_pin() {
+        let value: core::pin::Pin<Box<[u8; 16]>> = Box::pin([0; 16]);
+
+        assert!(!core::pin::Pin::<Box<[u8; 16]>>::IS_DYNAMIC);
+        let size = 16 + allocation_overhead(16);
+        assert_eq!(core::pin::Pin::<Box<[u8; 16]>>::STATIC_HEAP_SIZE, size);
+        assert_eq!(data_size(&value), size);
+
+        #[derive(DataSize)]
+        struct Task {
+            future: core::pin::Pin<Box<u64>>,
+            name: String,
+        }
+
+        let task = Task {
+            future: Box::pin(0),
+            name: "task".to_owned(),
+        };
+        assert_eq!(
+            data_size(&task),
+            8 + allocation_overhead(8) + 4 + allocation_overhead(4)
+        );
+    }
+
+    #[test]
+    fn test_once_cell() {
+        let cell: std::cell::OnceCell<Vec<u8>> = std::cell::OnceCell::new();
+        assert_eq!(data_size(&cell), 0);
+
+        cell.set(vec![0; 32]).unwrap();
+        assert_eq!(data_size(&cell), data_size(cell.get().unwrap()));
+        assert_eq!(data_size(&cell), 32 + allocation_overhead(32));
+    }
+
+    #[test]
+    fn test_once_lock() {
+        let lock: std::sync::OnceLock<Vec<u8>> = std::sync::OnceLock::new();
+        assert_eq!(data_size(&lock), 0);
+
+        lock.set(vec![0; 32]).unwrap();
+        assert_eq!(data_size(&lock), 32 + allocation_overhead(32));
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "count-unique-shared", feature = "strict-shared")))]
+    fn test_shared_str_is_not_counted() {
+        let arc: std::sync::Arc<str> = std::sync::Arc::from("hello");
+        let rc: std::rc::Rc<str> = std::rc::Rc::from("hello");
+
+        assert!(!std::sync::Arc::<str>::IS_DYNAMIC);
+        assert_eq!(data_size(&arc), 0);
+        assert_eq!(data_size(&rc), 0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "count-unique-shared", not(feature = "strict-shared")))]
+    fn test_unique_shared_str_is_counted() {
+        let arc: std::sync::Arc<str> = std::sync::Arc::from("hello");
+        let rc: std::rc::Rc<str> = std::rc::Rc::from("hello");
+
+        assert!(std::sync::Arc::<str>::IS_DYNAMIC);
+        assert_eq!(data_size(&arc), 5);
+        assert_eq!(data_size(&rc), 5);
+
+        // Once shared, neither handle counts the string.
+        let arc_clone = arc.clone();
+        let rc_clone = rc.clone();
+        assert_eq!(data_size(&arc), 0);
+        assert_eq!(data_size(&arc_clone), 0);
+        assert_eq!(data_size(&rc), 0);
+        assert_eq!(data_size(&rc_clone), 0);
+
+        drop(arc_clone);
+        assert_eq!(data_size(&arc), 5);
+
+        // Neither does a handle with weak references.
+        let weak = std::sync::Arc::downgrade(&arc);
+        assert_eq!(data_size(&arc), 0);
+        drop(weak);
+        assert_eq!(data_size(&arc), 5);
+    }
+
+    #[test]
+    fn test_count_arc() {
+        use crate::{count_arc, count_rc};
+        use std::{rc::Rc, sync::Arc};
+
+        #[derive(DataSize)]
+        struct Owner {
+            #[data_size(with = count_arc)]
+            shared: Arc<Vec<u8>>,
+            #[data_size(with = count_rc)]
+            local: Rc<u64>,
+        }
+
+        let owner = Owner {
+            shared: Arc::new(vec![0; 32]),
+            local: Rc::new(1),
+        };
+        let control_block = 2 * size_of::<usize>();
+        let shared = control_block + size_of::<Vec<u8>>();
+        let local = control_block + 8;
+
+        assert_eq!(
+            data_size(&owner),
+            shared
+                + allocation_overhead(shared)
+                + 32
+                + allocation_overhead(32)
+                + local
+                + allocation_overhead(local)
+        );
+
+        // Sharing does not change the estimate made through the owner.
+        let other = owner.shared.clone();
+        assert_eq!(count_arc(&other), count_arc(&owner.shared));
+        assert_eq!(count_rc(&owner.local), local + allocation_overhead(local));
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "count-unique-shared", feature = "strict-shared")))]
+    fn test_shared_slice_is_not_counted() {
+        let arc: std::sync::Arc<[u64]> = std::sync::Arc::from(vec![1, 2, 3]);
+        let rc: std::rc::Rc<[u64]> = std::rc::Rc::from(vec![1, 2, 3]);
+
+        assert!(!std::sync::Arc::<[u64]>::IS_DYNAMIC);
+        assert_eq!(data_size(&arc), 0);
+        assert_eq!(data_size(&rc), 0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "count-unique-shared", not(feature = "strict-shared")))]
+    fn test_unique_shared_slice_is_counted() {
+        let arc: std::sync::Arc<[u64]> = std::sync::Arc::from(vec![1, 2, 3]);
+        let rc: std::rc::Rc<[u64]> = std::rc::Rc::from(vec![1, 2, 3]);
+
+        assert_eq!(data_size(&arc), 24);
+        assert_eq!(data_size(&rc), 24);
+
+        let arc_clone = arc.clone();
+        assert_eq!(data_size(&arc), 0);
+        assert_eq!(data_size(&arc_clone), 0);
+
+        // Only the slice itself is counted, not the heap data owned by its elements.
+        let nested: std::sync::Arc<[Vec<u8>]> = std::sync::Arc::from(vec![vec![0; 16]]);
+        assert_eq!(data_size(&nested), size_of::<Vec<u8>>());
+
+        // The pointee does not need to implement `DataSize`.
+        struct Opaque(#[allow(dead_code)] u64);
+        assert_eq!(data_size(&std::rc::Rc::new(Opaque(0))), 8);
+    }
+
+    #[cfg(feature = "visitor")]
+    #[test]
+    fn test_visit_counts_allocations() {
+        use crate::DataSizeVisitor;
+
+        /// Counts heap allocations and keeps track of the deepest nesting level.
+        #[derive(Default)]
+        struct AllocationCounter {
+            allocations: usize,
+            bytes: usize,
+            depth: usize,
+            max_depth: usize,
+        }
+
+        impl DataSizeVisitor for AllocationCounter {
+            fn enter(&mut self, bytes: usize, _elements: usize) {
+                self.allocations += 1;
+                self.bytes += bytes;
+                self.depth += 1;
+                self.max_depth = self.max_depth.max(self.depth);
+            }
+
+            fn leave(&mut self) {
+                self.depth -= 1;
+            }
+        }
+
+        #[derive(DataSize)]
+        struct Leaf {
+            value: Box<u32>,
+            tag: u8,
+        }
+
+        #[derive(DataSize)]
+        enum Node {
+            Empty,
+            Leaves(Vec<Leaf>),
+            Boxed { inner: Box<Node> },
+        }
+
+        #[derive(DataSize)]
+        struct Tree {
+            nodes: Vec<Node>,
+            name: Option<Box<u64>>,
+            numbers: Vec<u16>,
+            empty: Vec<Leaf>,
+        }
+
+        let leaf = |v| Leaf {
+            value: Box::new(v),
+            tag: 0,
+        };
+
+        let tree = Tree {
+            // 1 buffer,
+            nodes: vec![
+                Node::Empty,
+                // 1 buffer + 2 boxes,
+                Node::Leaves(vec![leaf(1), leaf(2)]),
+                // 1 box + 1 buffer + 1 box.
+                Node::Boxed {
+                    inner: Box::new(Node::Leaves(vec![leaf(3)])),
+                },
+            ],
+            // 1 box,
+            name: Some(Box::new(42)),
+            // 1 buffer,
+            numbers: vec![1, 2, 3],
+            // and nothing for an unallocated `Vec`.
+            empty: Vec::new(),
+        };
+
+        let mut counter = AllocationCounter::default();
+        tree.visit(&mut counter);
+
+        assert_eq!(counter.allocations, 9);
+        assert_eq!(counter.depth, 0);
+        assert_eq!(counter.max_depth, 4);
+        assert_eq!(counter.bytes, data_size(&tree));
+
+        // Spare capacity is only reported if buffers are sized by it.
+        let mut counter = AllocationCounter::default();
+        Vec::<u16>::with_capacity(8).visit(&mut counter);
+        assert_eq!(counter.bytes, data_size(&Vec::<u16>::with_capacity(8)));
+        assert_eq!(counter.allocations, usize::from(counter.bytes > 0));
+    }
+
+    #[test]
+    fn test_borrowed_path() {
+        #[derive(DataSize)]
+        struct Config<'a> {
+            path: &'a std::path::Path,
+            name: &'a std::ffi::OsStr,
+            owned: std::path::PathBuf,
+        }
+
+        let owned = std::path::PathBuf::from("/etc/config.toml");
+        let config = Config {
+            path: owned.as_path(),
+            name: owned.file_name().unwrap(),
+            owned: owned.clone(),
+        };
+
+        assert_eq!(data_size(config.path), 0);
+        assert_eq!(data_size(config.name), 0);
+        assert_eq!(data_size(&config), data_size(&config.owned));
+    }
+
+    #[test]
+    fn test_cursor() {
+        let value = std::io::Cursor::new(vec![0u8; 100]);
+
+        assert_eq!(data_size(&value), 100 + allocation_overhead(100));
+    }
+
+    #[test]
+    fn test_include_stack() {
+        #[derive(DataSize)]
+        struct Chunks {
+            #[data_size(include_stack)]
+            data: Box<[u64]>,
+            count: u32,
+        }
+
+        #[derive(DataSize)]
+        struct Header {
+            #[data_size(include_stack)]
+            id: u64,
+            flags: u8,
+        }
+
+        #[derive(DataSize)]
+        enum Payload {
+            Inline(#[data_size(include_stack)] [u8; 16]),
+            Boxed {
+                #[data_size(include_stack)]
+                bytes: Box<[u8]>,
+            },
+        }
+
+        let stack = size_of::<Box<[u64]>>();
+        let data = stack + 32 + allocation_overhead(32);
+        let value = Chunks {
+            data: vec![0u64; 4].into_boxed_slice(),
+            count: 4,
+        };
+        assert_eq!(value.count, 4);
+        assert_eq!(data_size(&value), data);
+
+        // The stack size is constant, so it does not make a type dynamic.
+        assert!(!Header::IS_DYNAMIC);
+        assert_eq!(Header::STATIC_HEAP_SIZE, 8);
+        assert_eq!(data_size(&Header { id: 1, flags: 0 }), 8);
+
+        assert_eq!(data_size(&Payload::Inline([0; 16])), 16);
+        assert_eq!(
+            data_size(&Payload::Boxed {
+                bytes: vec![0u8; 10].into_boxed_slice()
+            }),
+            size_of::<Box<[u8]>>() + 10 + allocation_overhead(10)
+        );
+
+        #[cfg(feature = "detailed")]
+        {
+            let detailed = datasize::data_size_detailed(&value);
+            assert_eq!(detailed.total(), data_size(&value));
+            match detailed {
+                datasize::MemUsageNode::Detailed(ref members) => {
+                    assert_eq!(members["data"], datasize::MemUsageNode::Size(data));
+                }
+                ref node => panic!("expected detailed node, got {:?}", node),
+            }
+        }
+    }
+
+    #[test]
+    fn test_buffered_io() {
+        let reader = std::io::BufReader::with_capacity(4096, std::io::Cursor::new(vec![0u8; 100]));
+        let buffer = 4096 + allocation_overhead(4096);
+        assert_eq!(data_size(&reader), buffer + 100 + allocation_overhead(100));
+
+        let writer = std::io::BufWriter::with_capacity(1024, vec![0u8; 64]);
+        assert_eq!(
+            data_size(&writer),
+            1024 + allocation_overhead(1024) + 64 + allocation_overhead(64)
+        );
+
+        let reader = std::io::BufReader::with_capacity(4096, std::io::Cursor::new(Vec::new()));
+        assert_eq!(data_size(&reader), buffer);
+    }
+
+    #[test]
+    fn test_data_size_with_limit() {
+        let value: Vec<Vec<u8>> = vec![vec![0u8; 10]; 1000];
+        let outer = value.capacity() * core::mem::size_of::<Vec<u8>>();
+        let outer = outer + allocation_overhead(outer);
+
+        // A sufficient limit produces the exact estimate.
+        assert_eq!(
+            crate::data_size_with_limit(&value, 1000),
+            (data_size(&value), false)
+        );
+
+        // Only the first 100 inner vectors are visited, the outer allocation is known anyway.
+        assert_eq!(
+            crate::data_size_with_limit(&value, 100),
+            (outer + 100 * (10 + allocation_overhead(10)), true)
+        );
+        assert_eq!(crate::data_size_with_limit(&value, 0), (outer, true));
+    }
+
+    #[test]
+    fn test_data_size_with_limit_nested() {
+        #[derive(DataSize)]
+        struct Node {
+            label: String,
+            children: Vec<Node>,
+        }
+
+        let leaf = || Node {
+            label: "leaf".to_owned(),
+            children: Vec::new(),
+        };
+        let tree = Node {
+            label: "root".to_owned(),
+            children: vec![
+                Node {
+                    label: "inner".to_owned(),
+                    children: vec![leaf(), leaf()],
+                },
+                leaf(),
+            ],
+        };
+
+        // Every node but the root consumes part of the budget.
+        assert_eq!(
+            crate::data_size_with_limit(&tree, 4),
+            (data_size(&tree), false)
+        );
+
+        let (partial, exceeded) = crate::data_size_with_limit(&tree, 2);
+        assert!(exceeded);
+        assert!(partial < data_size(&tree));
+    }
+
+    /// A hasher without any state, standing in for third-party hashers.
+    #[derive(Default)]
+    struct NoopHasher(u64);
 
-    #[inline]
-    fn estimate_heap_size(&self) -> usize {
-        let size = estimate_hashbrown_rawtable::<(K, V)>(self.capacity());
+    impl std::hash::Hasher for NoopHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
 
-        if K::IS_DYNAMIC || V::IS_DYNAMIC {
-            size + self
-                .iter()
-                .map(|(k, v)| k.estimate_heap_size() + v.estimate_heap_size())
-                .sum::<usize>()
-        } else {
-            size + self.len() * (K::STATIC_HEAP_SIZE + V::STATIC_HEAP_SIZE)
+        fn write(&mut self, bytes: &[u8]) {
+            for &byte in bytes {
+                self.0 = self.0.rotate_left(8) ^ u64::from(byte);
+            }
         }
     }
-}
 
-impl<T, S> DataSize for std::collections::HashSet<T, S>
-where
-    T: DataSize,
-{
-    const IS_DYNAMIC: bool = true;
+    type NoopBuildHasher = std::hash::BuildHasherDefault<NoopHasher>;
 
-    const STATIC_HEAP_SIZE: usize = 0;
+    #[test]
+    fn test_hashmap_with_custom_hasher() {
+        use std::collections::{hash_map::RandomState, HashMap};
+
+        let mut random: HashMap<u64, u64, RandomState> = HashMap::with_hasher(RandomState::new());
+        let mut noop: HashMap<u64, u64, NoopBuildHasher> = HashMap::default();
+        for i in 0..100 {
+            random.insert(i, i);
+            noop.insert(i, i);
+        }
 
-    #[inline]
-    fn estimate_heap_size(&self) -> usize {
-        // HashSet<T> is based on HashMap<T, ()>
-        let size = estimate_hashbrown_rawtable::<(T, ())>(self.capacity());
+        // The hasher does not influence the estimate, only the table does.
+        assert_eq!(random.capacity(), noop.capacity());
+        assert_eq!(data_size(&random), data_size(&noop));
+        assert!(data_size(&noop) >= 100 * 16);
 
-        if T::IS_DYNAMIC {
-            size + self.iter().map(T::estimate_heap_size).sum::<usize>()
-        } else {
-            size + self.len() * T::STATIC_HEAP_SIZE
+        #[derive(DataSize)]
+        struct Index {
+            entries: HashMap<u64, u64, NoopBuildHasher>,
         }
+
+        let index = Index { entries: noop };
+        assert_eq!(data_size(&index), data_size(&index.entries));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate as datasize; // Required for the derive macro.
-    use crate::{data_size, DataSize};
-    use std::borrow::Cow;
+    #[test]
+    fn test_hashmap_sized_by_capacity() {
+        use std::collections::{HashMap, HashSet};
+
+        assert_eq!(data_size(&HashMap::<u64, u64>::new()), 0);
+        assert_eq!(data_size(&HashSet::<u64>::new()), 0);
+
+        // A reserved table is counted, even though it holds only a single entry.
+        let mut reserved: HashMap<u64, u64> = HashMap::with_capacity(1000);
+        reserved.insert(1, 1);
+        let len_based = reserved.len() * (2 * size_of::<u64>() + 1);
+        assert!(reserved.capacity() >= 1000);
+        assert!(data_size(&reserved) > len_based);
+        assert!(data_size(&reserved) >= 1000 * (2 * size_of::<u64>() + 1));
+
+        // Removing entries does not shrink the table.
+        let mut drained: HashMap<u64, u64> = (0..1000).map(|i| (i, i)).collect();
+        let full = data_size(&drained);
+        drained.retain(|&key, _| key == 0);
+        assert_eq!(data_size(&drained), full);
+
+        drained.shrink_to_fit();
+        assert!(data_size(&drained) < full);
+    }
 
     #[test]
-    fn test_box() {
-        let value: Box<u64> = Box::new(1234);
+    fn test_hashmap_reserved_then_shrunk() {
+        use std::collections::{HashMap, HashSet};
+
+        // Maps created without capacity have no table.
+        let empty: HashMap<u64, u64> = HashMap::with_capacity(0);
+        assert_eq!(empty.capacity(), 0);
+        assert_eq!(data_size(&empty), 0);
+        assert_eq!(data_size(&HashSet::<String>::with_capacity(0)), 0);
+
+        // Shrinking an empty map frees its table again.
+        let mut map: HashMap<u64, u64> = HashMap::with_capacity(0);
+        map.reserve(500);
+        let reserved = data_size(&map);
+        assert!(reserved >= 500 * 2 * size_of::<u64>());
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 0);
+        assert_eq!(data_size(&map), 0);
+
+        // A shrunk map is sized like a fresh map of the same capacity.
+        map.reserve(500);
+        map.extend((0..10).map(|i| (i, i)));
+        assert_eq!(data_size(&map), reserved);
+        map.shrink_to_fit();
+        assert!(data_size(&map) < reserved);
+        assert_eq!(
+            data_size(&map),
+            data_size(&HashMap::<u64, u64>::with_capacity(map.capacity()))
+        );
+
+        map.shrink_to(0);
+        assert_eq!(
+            data_size(&map),
+            data_size(&HashMap::<u64, u64>::with_capacity(map.capacity()))
+        );
 
-        assert_eq!(data_size::<Box<u64>>(&value), 8);
-        assert_eq!(data_size(&value), 8);
+        let mut set: HashSet<String> = HashSet::with_capacity(0);
+        set.reserve(100);
+        set.insert("only".to_owned());
+        set.shrink_to_fit();
+        assert_eq!(
+            data_size(&set),
+            data_size(&HashSet::<String>::with_capacity(set.capacity()))
+                + 4
+                + allocation_overhead(4)
+        );
     }
 
     #[test]
-    fn test_option_box() {
-        let value_none: Option<Box<u64>> = None;
-        let value_some: Option<Box<u64>> = Some(Box::new(12345));
+    #[cfg(not(feature = "vec-len-sizing"))]
+    fn test_hashmap_dynamic_keys() {
+        use std::collections::HashMap;
 
-        assert_eq!(data_size::<Option<Box<u64>>>(&value_none), 0);
-        assert_eq!(data_size::<Option<Box<u64>>>(&value_some), 8);
+        let mut map: HashMap<String, u64> = HashMap::new();
+        let mut key = String::with_capacity(32);
+        key.push_str("first");
+        map.insert(key, 1);
+        map.insert("second".to_owned(), 2);
+
+        let table = data_size(&HashMap::<String, u64>::with_capacity(map.capacity()));
+
+        // The capacities of the keys are counted, not their lengths.
+        assert_eq!(
+            data_size(&map),
+            table + 32 + allocation_overhead(32) + 6 + allocation_overhead(6)
+        );
+
+        let mut values: HashMap<u64, String> = HashMap::new();
+        values.insert(1, "value".to_owned());
+        assert_eq!(
+            data_size(&values),
+            data_size(&HashMap::<u64, String>::with_capacity(values.capacity()))
+                + 5
+                + allocation_overhead(5)
+        );
     }
 
     #[test]
-    fn test_cow() {
-        let value: Cow<'static, str> = Cow::from("hello");
-        assert_eq!(data_size(&value), 0);
-
-        let value_owned: Cow<'static, str> = Cow::from("hello".to_owned());
+    fn test_hashset_with_custom_hasher() {
+        use std::collections::{hash_map::RandomState, HashSet};
+
+        // Dynamic elements are visited one by one.
+        let mut names: HashSet<String, NoopBuildHasher> = HashSet::default();
+        names.insert("alpha".to_owned());
+        names.insert("beta".to_owned());
+        let table = data_size(
+            &HashSet::<String, NoopBuildHasher>::with_capacity_and_hasher(
+                names.capacity(),
+                NoopBuildHasher::default(),
+            ),
+        );
+        assert!(HashSet::<String, NoopBuildHasher>::IS_DYNAMIC);
+        assert_eq!(
+            data_size(&names),
+            table + 5 + allocation_overhead(5) + 4 + allocation_overhead(4)
+        );
 
-        assert_eq!(data_size(&value_owned), data_size(&"hello".to_owned()));
+        // Non-dynamic elements are covered by the table alone.
+        let mut random: HashSet<u64, RandomState> = HashSet::with_hasher(RandomState::new());
+        let mut noop: HashSet<u64, NoopBuildHasher> = HashSet::default();
+        for i in 0..100 {
+            random.insert(i);
+            noop.insert(i);
+        }
+        assert_eq!(random.capacity(), noop.capacity());
+        assert_eq!(data_size(&random), data_size(&noop));
+        assert_eq!(
+            data_size(&random),
+            data_size(&HashSet::<u64>::with_capacity(random.capacity()))
+        );
     }
 
     #[test]
-    fn test_string() {
-        let value = "abcdef".to_string();
+    fn test_hashmap_hasher_state() {
+        use std::collections::{HashMap, HashSet};
+        use std::hash::{BuildHasher, Hasher};
+
+        /// A hasher state holding a key on the heap.
+        #[derive(DataSize)]
+        struct KeyedState {
+            key: Vec<u8>,
+        }
+
+        impl BuildHasher for KeyedState {
+            type Hasher = NoopHasher;
+
+            fn build_hasher(&self) -> NoopHasher {
+                let mut hasher = NoopHasher::default();
+                hasher.write(&self.key);
+                hasher
+            }
+        }
 
-        assert_eq!(data_size(&value), 6);
+        let mut keyed = HashMap::with_hasher(KeyedState { key: vec![0; 64] });
+        let mut noop: HashMap<u64, u64, NoopBuildHasher> = HashMap::default();
+        let mut keyed_set = HashSet::with_hasher(KeyedState { key: vec![0; 64] });
+        let mut noop_set: HashSet<u64, NoopBuildHasher> = HashSet::default();
+        for i in 0..10 {
+            keyed.insert(i, i);
+            noop.insert(i, i);
+            keyed_set.insert(i);
+            noop_set.insert(i);
+        }
+
+        // The heap data of the hasher state is only counted if enabled.
+        let hasher_size = if cfg!(feature = "count-hasher-state") {
+            64 + allocation_overhead(64)
+        } else {
+            0
+        };
+        assert_eq!(data_size(&keyed), data_size(&noop) + hasher_size);
+        assert_eq!(data_size(&keyed_set), data_size(&noop_set) + hasher_size);
     }
 
     #[test]
@@ -334,11 +1667,18 @@ mod tests {
 
         // Add a `warning` will cause a heap allocation.
         ex.warning = Some(Box::new(12345));
-        assert_eq!(data_size(&ex), 4);
+        let warning = 4 + allocation_overhead(4);
+        assert_eq!(data_size(&ex), warning);
 
         // Let's reserve some capacity on `my_data`.
         ex.my_data.reserve_exact(10);
-        assert_eq!(data_size(&ex), 4 + 10 * 8)
+        #[cfg(not(feature = "vec-len-sizing"))]
+        assert_eq!(
+            data_size(&ex),
+            warning + 10 * 8 + allocation_overhead(10 * 8)
+        );
+        #[cfg(feature = "vec-len-sizing")]
+        assert_eq!(data_size(&ex), warning + allocation_overhead(10 * 8));
     }
 
     #[test]
@@ -355,6 +1695,7 @@ mod tests {
             },
             Bert(Vec<u32>, #[data_size(skip)] Vec<u8>),
             #[data_size(skip)]
+            #[allow(dead_code)]
             Skipped(Vec<i32>),
         }
 
@@ -366,10 +1707,10 @@ mod tests {
             nonheap: 99,
             _extra: Box::new(456),
         };
-        assert_eq!(data_size(&baz), 4);
+        assert_eq!(data_size(&baz), 4 + allocation_overhead(4));
 
         let bert = Foo::Bert(vec![5, 6, 7, 8, 9], vec![1, 2, 3, 4, 5]);
-        assert_eq!(data_size(&bert), 5 * 4);
+        assert_eq!(data_size(&bert), 5 * 4 + allocation_overhead(5 * 4));
 
         let skipped = Foo::Skipped(vec![-1, 1, 99, 100]);
         assert_eq!(data_size(&skipped), 0);
@@ -396,14 +1737,17 @@ mod tests {
             b: None,
             c: 123,
         };
-        assert_eq!(data_size(&a), 4);
+        assert_eq!(data_size(&a), 4 + allocation_overhead(4));
 
         let both: Example<Box<u32>, Box<u8>> = Example {
             a: Some(Box::new(0)),
             b: Some(Box::new(0)),
             c: 123,
         };
-        assert_eq!(data_size(&both), 5);
+        assert_eq!(
+            data_size(&both),
+            4 + allocation_overhead(4) + 1 + allocation_overhead(1)
+        );
     }
 
     #[test]
@@ -441,8 +1785,12 @@ mod tests {
         let boxed = Box::new(inner.clone());
 
         let inner_size = core::mem::size_of::<Inner>();
-        assert_eq!(8, data_size(&inner));
-        assert_eq!(8 + inner_size, data_size(&boxed));
+        let value = 8 + allocation_overhead(8);
+        assert_eq!(value, data_size(&inner));
+        assert_eq!(
+            value + inner_size + allocation_overhead(inner_size),
+            data_size(&boxed)
+        );
     }
 
     #[test]
@@ -479,11 +1827,18 @@ mod tests {
         use std::collections::HashMap;
 
         let mut inner_map = HashMap::new();
-        inner_map.insert("value", MemUsageNode::Size(8));
+        inner_map.insert("value", MemUsageNode::Size(8 + allocation_overhead(8)));
         inner_map.insert("dummy", MemUsageNode::Size(0));
 
+        let mut boxed_map = HashMap::new();
+        boxed_map.insert(
+            "allocation",
+            MemUsageNode::Size(16 + allocation_overhead(16)),
+        );
+        boxed_map.insert("value", MemUsageNode::Detailed(inner_map.clone()));
+
         let mut outer_map = HashMap::new();
-        outer_map.insert("a", MemUsageNode::Size(24));
+        outer_map.insert("a", MemUsageNode::Detailed(boxed_map));
         outer_map.insert("b", MemUsageNode::Detailed(inner_map));
         outer_map.insert("c", MemUsageNode::Size(0));
 
@@ -494,6 +1849,438 @@ mod tests {
         assert_eq!(data_size(&fixture), detailed.total());
     }
 
+    #[test]
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    fn test_detailed_with() {
+        use datasize::MemUsageNode;
+        use std::collections::HashMap;
+
+        /// Reports the keys and values of the pairs separately.
+        fn detailed_pairs(pairs: &[(String, Vec<u8>)]) -> MemUsageNode {
+            let mut members = HashMap::new();
+            members.insert(
+                "keys",
+                MemUsageNode::Size(pairs.iter().map(|(key, _)| data_size(key)).sum()),
+            );
+            members.insert(
+                "values",
+                MemUsageNode::Size(pairs.iter().map(|(_, value)| data_size(value)).sum()),
+            );
+            MemUsageNode::Detailed(members)
+        }
+
+        #[derive(DataSize)]
+        struct Store {
+            #[data_size(detailed_with = detailed_pairs)]
+            pairs: Vec<(String, Vec<u8>)>,
+            name: String,
+        }
+
+        let store = Store {
+            pairs: vec![("key".to_owned(), vec![0; 10])],
+            name: "store".to_owned(),
+        };
+
+        let mut pairs_map = HashMap::new();
+        pairs_map.insert("keys", MemUsageNode::Size(3 + allocation_overhead(3)));
+        pairs_map.insert("values", MemUsageNode::Size(10 + allocation_overhead(10)));
+
+        let mut expected = HashMap::new();
+        expected.insert("pairs", MemUsageNode::Detailed(pairs_map));
+        expected.insert("name", MemUsageNode::Size(5 + allocation_overhead(5)));
+
+        assert_eq!(
+            datasize::data_size_detailed(&store),
+            MemUsageNode::Detailed(expected)
+        );
+
+        // The flat estimate is still derived as usual.
+        let pair = size_of::<(String, Vec<u8>)>();
+        assert_eq!(
+            data_size(&store),
+            pair + allocation_overhead(pair)
+                + 3
+                + allocation_overhead(3)
+                + 10
+                + allocation_overhead(10)
+                + 5
+                + allocation_overhead(5)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    fn test_detailed_time_types() {
+        use datasize::MemUsageNode;
+        use std::collections::HashMap;
+        use std::time::{Duration, Instant, SystemTime};
+
+        #[derive(DataSize)]
+        struct Timing {
+            started: Instant,
+            wall_clock: SystemTime,
+            timeout: Duration,
+            label: String,
+        }
+
+        let timing = Timing {
+            started: Instant::now(),
+            wall_clock: SystemTime::now(),
+            timeout: Duration::from_secs(5),
+            label: "job".to_owned(),
+        };
+
+        let mut expected = HashMap::new();
+        expected.insert("started", MemUsageNode::Size(0));
+        expected.insert("wall_clock", MemUsageNode::Size(0));
+        expected.insert("timeout", MemUsageNode::Size(0));
+        expected.insert("label", MemUsageNode::Size(3 + allocation_overhead(3)));
+        assert_eq!(
+            datasize::data_size_detailed(&timing),
+            MemUsageNode::Detailed(expected)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    fn test_detailed_collections() {
+        use datasize::MemUsageNode;
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Clone, DataSize)]
+        struct Entry {
+            name: String,
+            tags: Vec<u32>,
+        }
+
+        let entries = vec![
+            Entry {
+                name: "first".to_owned(),
+                tags: vec![1, 2],
+            },
+            Entry {
+                name: "second".to_owned(),
+                tags: vec![3],
+            },
+        ];
+
+        // All elements are aggregated into a single node, regardless of their number.
+        let mut elements = HashMap::new();
+        elements.insert(
+            "name",
+            MemUsageNode::Size(5 + allocation_overhead(5) + 6 + allocation_overhead(6)),
+        );
+        elements.insert(
+            "tags",
+            MemUsageNode::Size(8 + allocation_overhead(8) + 4 + allocation_overhead(4)),
+        );
+        let buffer = 2 * size_of::<Entry>();
+        let mut expected = HashMap::new();
+        expected.insert(
+            "buffer",
+            MemUsageNode::Size(buffer + allocation_overhead(buffer)),
+        );
+        expected.insert("elements", MemUsageNode::Detailed(elements.clone()));
+
+        let detailed = datasize::data_size_detailed(&entries);
+        assert_eq!(detailed, MemUsageNode::Detailed(expected));
+        assert_eq!(detailed.total(), data_size(&entries));
+
+        // Arrays have no buffer of their own.
+        let array = [entries[0].clone(), entries[1].clone()];
+        assert_eq!(
+            datasize::data_size_detailed(&array),
+            MemUsageNode::Detailed(elements.clone())
+        );
+
+        // Elements without details collapse into a single size.
+        let buffer = 2 * size_of::<Vec<u8>>();
+        assert_eq!(
+            datasize::data_size_detailed(&vec![vec![1u8; 4]; 2]),
+            MemUsageNode::Size(
+                buffer + allocation_overhead(buffer) + 2 * (4 + allocation_overhead(4))
+            )
+        );
+
+        let map: HashMap<u8, Entry> = entries
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, e)| (i as u8, e))
+            .collect();
+        let detailed = datasize::data_size_detailed(&map);
+        let mut entries = HashMap::new();
+        entries.insert("keys", MemUsageNode::Size(0));
+        entries.insert("values", MemUsageNode::Detailed(elements));
+        match detailed {
+            MemUsageNode::Detailed(ref members) => {
+                assert_eq!(members["entries"], MemUsageNode::Detailed(entries));
+            }
+            _ => panic!("expected detailed node"),
+        }
+        assert_eq!(detailed.total(), data_size(&map));
+
+        let tree: BTreeMap<u8, Entry> = map.into_iter().collect();
+        assert_eq!(
+            datasize::data_size_detailed(&tree).total(),
+            data_size(&tree)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    fn test_detailed_hash_map() {
+        use datasize::MemUsageNode;
+        use std::collections::HashMap;
+
+        let map: HashMap<u64, String> = (0..10).map(|i| (i, "x".repeat(i as usize))).collect();
+
+        let detailed = datasize::data_size_detailed(&map);
+        match detailed {
+            MemUsageNode::Detailed(ref members) => {
+                assert_eq!(members.len(), 2);
+                // Only the nine non-empty strings allocate.
+                assert_eq!(
+                    members["entries"],
+                    MemUsageNode::Size(45 + 9 * allocation_overhead(1))
+                );
+                assert_eq!(
+                    members["table"].total() + members["entries"].total(),
+                    data_size(&map)
+                );
+            }
+            _ => panic!("expected detailed node"),
+        }
+
+        // Even maps without any entry heap data report their table separately.
+        let mut expected = HashMap::new();
+        expected.insert("table", MemUsageNode::Size(0));
+        expected.insert("entries", MemUsageNode::Size(0));
+        assert_eq!(
+            datasize::data_size_detailed(&HashMap::<u8, u8>::new()),
+            MemUsageNode::Detailed(expected)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "detailed-spare")]
+    fn test_detailed_spare() {
+        use datasize::MemUsageNode;
+        use std::collections::HashMap;
+
+        let mut data: Vec<u64> = Vec::with_capacity(10);
+        data.extend([1, 2, 3]);
+
+        let mut expected = HashMap::new();
+        // The allocator overhead is not used by any element, and thus counted as spare.
+        expected.insert("used", MemUsageNode::Size(24));
+        #[cfg(not(feature = "vec-len-sizing"))]
+        expected.insert("spare", MemUsageNode::Size(56 + allocation_overhead(80)));
+        #[cfg(feature = "vec-len-sizing")]
+        expected.insert("spare", MemUsageNode::Size(allocation_overhead(80)));
+        expected.insert("elements", MemUsageNode::Size(0));
+
+        let detailed = datasize::data_size_detailed(&data);
+        assert_eq!(detailed, MemUsageNode::Detailed(expected));
+        assert_eq!(detailed.total(), data_size(&data));
+
+        let mut text = String::with_capacity(16);
+        text.push_str("abc");
+        match datasize::data_size_detailed(&text) {
+            MemUsageNode::Detailed(ref members) => {
+                assert_eq!(members["used"], MemUsageNode::Size(3));
+                assert_eq!(members["spare"].total(), data_size(&text) - 3);
+            }
+            ref node => panic!("expected detailed node, got {:?}", node),
+        }
+
+        let map: HashMap<u32, u32> = (0..3).map(|i| (i, i)).collect();
+        let detailed = datasize::data_size_detailed(&map);
+        match detailed {
+            MemUsageNode::Detailed(ref members) => {
+                assert_eq!(members["used"], MemUsageNode::Size(24));
+                assert_eq!(members["entries"], MemUsageNode::Size(0));
+            }
+            ref node => panic!("expected detailed node, got {:?}", node),
+        }
+        assert_eq!(detailed.total(), data_size(&map));
+    }
+
+    #[test]
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    fn test_detailed_positional_keys() {
+        use datasize::MemUsageNode;
+        use std::collections::HashMap;
+
+        #[derive(DataSize)]
+        struct Foo(Vec<u8>, String);
+
+        #[derive(DataSize)]
+        enum Bar {
+            Empty,
+            Pair(Box<u32>, #[data_size(skip)] u8, Vec<u8>),
+            Named { items: Vec<u16> },
+        }
+
+        let mut foo_map = HashMap::new();
+        foo_map.insert("0", MemUsageNode::Size(4 + allocation_overhead(4)));
+        foo_map.insert("1", MemUsageNode::Size(5 + allocation_overhead(5)));
+        assert_eq!(
+            datasize::data_size_detailed(&Foo(vec![1, 2, 3, 4], "hello".to_owned())),
+            MemUsageNode::Detailed(foo_map)
+        );
+
+        let mut pair_map = HashMap::new();
+        pair_map.insert("0", MemUsageNode::Size(4 + allocation_overhead(4)));
+        pair_map.insert("2", MemUsageNode::Size(2 + allocation_overhead(2)));
+        let mut pair_variant = HashMap::new();
+        pair_variant.insert("Bar::Pair", MemUsageNode::Detailed(pair_map));
+        assert_eq!(
+            datasize::data_size_detailed(&Bar::Pair(Box::new(1), 2, vec![3, 4])),
+            MemUsageNode::Detailed(pair_variant)
+        );
+
+        let mut named_map = HashMap::new();
+        named_map.insert("items", MemUsageNode::Size(4 + allocation_overhead(4)));
+
+        let mut named_variant = HashMap::new();
+        named_variant.insert("Bar::Named", MemUsageNode::Detailed(named_map));
+        let named = Bar::Named { items: vec![1, 2] };
+        assert_eq!(
+            datasize::data_size_detailed(&named),
+            MemUsageNode::Detailed(named_variant)
+        );
+        assert_eq!(
+            data_size(&named),
+            datasize::data_size_detailed(&named).total()
+        );
+
+        let mut empty_variant = HashMap::new();
+        empty_variant.insert("Bar::Empty", MemUsageNode::Size(0));
+        assert_eq!(
+            datasize::data_size_detailed(&Bar::Empty),
+            MemUsageNode::Detailed(empty_variant)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "detailed")]
+    fn test_data_size_fields() {
+        use datasize::MemUsageNode;
+
+        #[derive(DataSize)]
+        struct Record {
+            id: u64,
+            name: String,
+            #[data_size(skip)]
+            #[allow(dead_code)]
+            cache: Vec<u8>,
+            tags: Vec<String>,
+        }
+
+        #[derive(DataSize)]
+        #[allow(dead_code)]
+        struct Pair(Vec<u8>, #[data_size(skip)] u8, String);
+
+        #[derive(DataSize)]
+        #[data_size(transparent)]
+        struct Wrapper(Record);
+
+        #[derive(DataSize)]
+        enum State {
+            Idle,
+            Running { pid: u32 },
+        }
+
+        assert_eq!(Record::DATA_SIZE_FIELDS, &["id", "name", "tags"]);
+        assert_eq!(Pair::DATA_SIZE_FIELDS, &["0", "2"]);
+        assert_eq!(Wrapper::DATA_SIZE_FIELDS, Record::DATA_SIZE_FIELDS);
+        assert_eq!(State::DATA_SIZE_FIELDS, &["State::Idle", "State::Running"]);
+        assert!(Vec::<u8>::DATA_SIZE_FIELDS.is_empty());
+
+        // The fields match the keys of the detailed estimate.
+        let record = Record {
+            id: 1,
+            name: "record".to_owned(),
+            cache: Vec::new(),
+            tags: vec!["a".to_owned()],
+        };
+        match datasize::data_size_detailed(&record) {
+            MemUsageNode::Detailed(members) => {
+                let mut keys: Vec<_> = members.keys().copied().collect();
+                keys.sort_unstable();
+                assert_eq!(keys, Record::DATA_SIZE_FIELDS);
+            }
+            _ => panic!("expected detailed node"),
+        }
+
+        for state in [State::Idle, State::Running { pid: 1 }].iter() {
+            match datasize::data_size_detailed(state) {
+                MemUsageNode::Detailed(variants) => {
+                    assert!(variants
+                        .keys()
+                        .all(|key| State::DATA_SIZE_FIELDS.contains(key)));
+                }
+                _ => panic!("expected detailed node"),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "detailed", not(feature = "detailed-spare")))]
+    fn test_detailed_enum_variants() {
+        use datasize::MemUsageNode;
+
+        #[derive(DataSize)]
+        enum Shape {
+            Point,
+            Polygon { corners: Vec<u32>, label: String },
+            Circle(Box<u64>),
+        }
+
+        let polygon = Shape::Polygon {
+            corners: vec![1, 2, 3],
+            label: "tri".to_owned(),
+        };
+        let detailed = datasize::data_size_detailed(&polygon);
+        match detailed {
+            MemUsageNode::Detailed(ref variants) => {
+                // Only the active variant is reported.
+                assert_eq!(variants.len(), 1);
+                assert!(!variants.contains_key("Shape::Point"));
+                assert!(!variants.contains_key("Shape::Circle"));
+
+                match variants["Shape::Polygon"] {
+                    MemUsageNode::Detailed(ref fields) => {
+                        assert_eq!(
+                            fields["corners"],
+                            MemUsageNode::Size(12 + allocation_overhead(12))
+                        );
+                        assert_eq!(
+                            fields["label"],
+                            MemUsageNode::Size(3 + allocation_overhead(3))
+                        );
+                    }
+                    _ => panic!("expected detailed node"),
+                }
+            }
+            _ => panic!("expected detailed node"),
+        }
+        assert_eq!(detailed.total(), data_size(&polygon));
+
+        let circle = datasize::data_size_detailed(&Shape::Circle(Box::new(1)));
+        assert_eq!(circle.total(), 8 + allocation_overhead(8));
+        match circle {
+            MemUsageNode::Detailed(ref variants) => {
+                assert!(variants.contains_key("Shape::Circle"));
+                assert!(!variants.contains_key("Shape::Polygon"));
+            }
+            _ => panic!("expected detailed node"),
+        }
+
+        assert_eq!(datasize::data_size_detailed(&Shape::Point).total(), 0);
+    }
+
     #[test]
     fn test_generic_enum() {
         #[derive(DataSize)]
@@ -506,6 +2293,7 @@ mod tests {
             },
             Bert(Vec<A>, #[data_size(skip)] Vec<D>, Box<A>),
             #[data_size(skip)]
+            #[allow(dead_code)]
             Skipped(Vec<C>),
         }
 
@@ -513,36 +2301,94 @@ mod tests {
             boxed: Box::new(123),
             extra: Box::new(456),
         };
-        assert_eq!(data_size(&baz), 1);
+        assert_eq!(data_size(&baz), 1 + allocation_overhead(1));
 
         let bert: Foo<u8, u16, u32, u64> =
             Foo::Bert(vec![5, 6, 7, 8, 9], vec![1, 2, 3, 4, 5], Box::new(1));
-        assert_eq!(data_size(&bert), 5 + 1);
+        assert_eq!(
+            data_size(&bert),
+            5 + allocation_overhead(5) + 1 + allocation_overhead(1)
+        );
 
         let skipped: Foo<u8, u16, u32, u64> = Foo::Skipped(vec![1, 1, 99, 100]);
         assert_eq!(data_size(&skipped), 0);
     }
 
+    #[test]
+    fn test_recursive_tree() {
+        // `Vec<Tree>` is dynamic regardless of `Tree`, so the constants do not depend on themselves.
+        #[derive(DataSize)]
+        struct Tree {
+            label: String,
+            children: Vec<Tree>,
+        }
+
+        fn node(label: &str, children: Vec<Tree>) -> Tree {
+            Tree {
+                label: label.to_owned(),
+                children,
+            }
+        }
+
+        assert!(Tree::IS_DYNAMIC);
+        assert_eq!(Tree::STATIC_HEAP_SIZE, 0);
+
+        let leaf = node("leaf", Vec::new());
+        /// The size of a single allocation of `bytes` bytes.
+        fn allocation(bytes: usize) -> usize {
+            bytes + allocation_overhead(bytes)
+        }
+
+        assert_eq!(data_size(&leaf), allocation(4));
+
+        let tree = node(
+            "root",
+            vec![leaf, node("inner", vec![node("x", Vec::new())])],
+        );
+        assert_eq!(
+            data_size(&tree),
+            allocation(4)
+                + allocation(2 * size_of::<Tree>())
+                + allocation(4)
+                + allocation(5)
+                + allocation(size_of::<Tree>())
+                + allocation(1)
+        );
+    }
+
     #[test]
     fn test_generic_newtype_struct() {
         #[derive(DataSize)]
         struct Foo<T>(T);
 
         assert!(!Foo::<Box<u32>>::IS_DYNAMIC);
-        assert_eq!(Foo::<Box<u32>>::STATIC_HEAP_SIZE, 4);
-        assert_eq!(data_size(&Foo(Box::new(123u32))), 4);
+        assert_eq!(
+            Foo::<Box<u32>>::STATIC_HEAP_SIZE,
+            4 + allocation_overhead(4)
+        );
+        assert_eq!(
+            data_size(&Foo(Box::new(123u32))),
+            4 + allocation_overhead(4)
+        );
     }
 
     #[test]
     fn test_generic_tuple_struct() {
         #[derive(DataSize)]
-        struct Foo<T>(T, Box<u8>, #[data_size(skip)] Box<u32>);
+        struct Foo<T>(
+            T,
+            Box<u8>,
+            #[data_size(skip)]
+            #[allow(dead_code)]
+            Box<u32>,
+        );
 
         assert!(!Foo::<Box<u32>>::IS_DYNAMIC);
-        assert_eq!(Foo::<Box<u32>>::STATIC_HEAP_SIZE, 5);
+        let size = 4 + allocation_overhead(4) + 1 + allocation_overhead(1);
+        assert_eq!(Foo::<Box<u32>>::STATIC_HEAP_SIZE, size);
         assert_eq!(
             data_size(&Foo(Box::new(123u32), Box::new(45), Box::new(0))),
-            5
+            size
         );
     }
 }
@@ -0,0 +1,43 @@
+use super::{non_dynamic_const_heap_size, DataSize};
+
+non_dynamic_const_heap_size!(
+    time::OffsetDateTime
+    time::PrimitiveDateTime
+    time::Date
+    time::Time
+    time::Duration,
+    0
+);
+
+#[cfg(test)]
+mod tests {
+    use crate as datasize; // Required for the derive macro.
+    use crate::{allocation_overhead, data_size, DataSize};
+    use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+    #[test]
+    fn test_date_times() {
+        #[derive(DataSize)]
+        struct Schedule {
+            events: Vec<OffsetDateTime>,
+            start: PrimitiveDateTime,
+            timeout: Duration,
+        }
+
+        let date = Date::from_calendar_date(2020, Month::January, 1).unwrap();
+        let schedule = Schedule {
+            events: vec![
+                OffsetDateTime::UNIX_EPOCH,
+                OffsetDateTime::UNIX_EPOCH + Duration::DAY,
+            ],
+            start: PrimitiveDateTime::new(date, Time::MIDNIGHT),
+            timeout: Duration::seconds(30),
+        };
+
+        assert!(!OffsetDateTime::IS_DYNAMIC);
+        assert!(!Date::IS_DYNAMIC);
+        assert!(!Time::IS_DYNAMIC);
+        let events = 2 * core::mem::size_of::<OffsetDateTime>();
+        assert_eq!(data_size(&schedule), events + allocation_overhead(events));
+    }
+}
@@ -0,0 +1,105 @@
+use super::{is_dynamic, sum_with_limit, DataSize, Limit};
+
+// An `ArrayVec` stores its elements inline, so only the heap data of the elements themselves is
+// counted.
+impl<A> DataSize for tinyvec::ArrayVec<A>
+where
+    A: tinyvec::Array,
+    A::Item: DataSize,
+{
+    const IS_DYNAMIC: bool = A::Item::IS_DYNAMIC || A::Item::STATIC_HEAP_SIZE > 0;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        if is_dynamic::<A::Item>() {
+            self.iter().map(DataSize::estimate_heap_size).sum()
+        } else {
+            self.len() * A::Item::STATIC_HEAP_SIZE
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        if is_dynamic::<A::Item>() {
+            sum_with_limit(self.iter(), limit)
+        } else {
+            self.len() * A::Item::STATIC_HEAP_SIZE
+        }
+    }
+}
+
+impl<A> DataSize for tinyvec::TinyVec<A>
+where
+    A: tinyvec::Array,
+    A::Item: DataSize,
+{
+    const IS_DYNAMIC: bool = true;
+
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        // Once spilled, a `TinyVec` is backed by a regular `Vec`.
+        match self {
+            tinyvec::TinyVec::Inline(inline) => inline.estimate_heap_size(),
+            tinyvec::TinyVec::Heap(heap) => heap.estimate_heap_size(),
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        match self {
+            tinyvec::TinyVec::Inline(inline) => inline.estimate_heap_size_with_limit(limit),
+            tinyvec::TinyVec::Heap(heap) => heap.estimate_heap_size_with_limit(limit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_array_vec() {
+        let mut plain: tinyvec::ArrayVec<[u64; 4]> = tinyvec::ArrayVec::new();
+        plain.push(1);
+        plain.push(2);
+
+        assert!(!tinyvec::ArrayVec::<[u64; 4]>::IS_DYNAMIC);
+        assert_eq!(data_size(&plain), 0);
+
+        let mut nested: tinyvec::ArrayVec<[Vec<u8>; 4]> = tinyvec::ArrayVec::new();
+        nested.push(vec![0; 16]);
+        nested.push(vec![0; 8]);
+
+        assert!(tinyvec::ArrayVec::<[Vec<u8>; 4]>::IS_DYNAMIC);
+        assert_eq!(
+            data_size(&nested),
+            16 + allocation_overhead(16) + 8 + allocation_overhead(8)
+        );
+    }
+
+    #[test]
+    fn test_tiny_vec_inline() {
+        let mut value: tinyvec::TinyVec<[u64; 4]> = tinyvec::TinyVec::new();
+        value.extend([1, 2, 3]);
+
+        assert!(value.is_inline());
+        assert_eq!(data_size(&value), 0);
+    }
+
+    #[test]
+    fn test_tiny_vec_heap() {
+        let mut value: tinyvec::TinyVec<[u64; 4]> = tinyvec::TinyVec::new();
+        value.extend([1, 2, 3, 4, 5]);
+
+        assert!(value.is_heap());
+        let overhead = allocation_overhead(value.capacity() * 8);
+        #[cfg(not(feature = "vec-len-sizing"))]
+        assert_eq!(data_size(&value), value.capacity() * 8 + overhead);
+        #[cfg(feature = "vec-len-sizing")]
+        assert_eq!(data_size(&value), 5 * 8 + overhead);
+    }
+}
@@ -0,0 +1,199 @@
+//! Memoization of heap size estimates.
+
+use core::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+
+use super::{DataSize, Limit};
+
+/// A wrapper that caches the heap size estimate of its value.
+///
+/// The estimate is calculated lazily the first time the value is sized and reused afterwards,
+/// which is useful for large, rarely modified structures. Any mutable access through `DerefMut`
+/// invalidates the cached estimate, as does calling `invalidate`.
+///
+/// Note that the cache cannot detect modifications made through a shared reference, i.e. through
+/// interior mutability of `T` like a `RefCell` or `Mutex`. In that case, `invalidate` must be
+/// called manually, otherwise a stale estimate is reported.
+///
+/// The estimate is cached in a `Cell`, so a `Tracked` is `Send` if `T` is, but never `Sync`, even
+/// if `T` is. A wrapped value cannot be shared between threads, e.g. through an `Arc`.
+#[derive(Clone, Debug, Default)]
+pub struct Tracked<T> {
+    /// The wrapped value.
+    value: T,
+    /// The cached heap size estimate, if any.
+    cached: Cell<Option<usize>>,
+}
+
+impl<T> Tracked<T> {
+    /// Wraps a value, without calculating its estimate yet.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Tracked {
+            value,
+            cached: Cell::new(None),
+        }
+    }
+
+    /// Discards the cached estimate, causing it to be recalculated the next time it is needed.
+    #[inline]
+    pub fn invalidate(&mut self) {
+        self.cached.set(None);
+    }
+
+    /// Returns the wrapped value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for Tracked<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Tracked::new(value)
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // The value may be modified through the returned reference, so we have to assume it is.
+        self.invalidate();
+        &mut self.value
+    }
+}
+
+impl<T> DataSize for Tracked<T>
+where
+    T: DataSize,
+{
+    const IS_DYNAMIC: bool = T::IS_DYNAMIC;
+
+    const STATIC_HEAP_SIZE: usize = T::STATIC_HEAP_SIZE;
+
+    #[inline]
+    fn estimate_heap_size(&self) -> usize {
+        match self.cached.get() {
+            Some(size) => size,
+            None => {
+                let size = self.value.estimate_heap_size();
+                self.cached.set(Some(size));
+                size
+            }
+        }
+    }
+
+    #[inline]
+    fn estimate_heap_size_with_limit(&self, limit: &mut Limit) -> usize {
+        // A limited estimate may be partial, so it is never cached.
+        match self.cached.get() {
+            Some(size) => size,
+            None => self.value.estimate_heap_size_with_limit(limit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::Tracked;
+    use crate::{data_size, DataSize};
+
+    /// A value counting how often it was sized.
+    struct Counted {
+        size: Cell<usize>,
+        calls: Cell<usize>,
+    }
+
+    impl Counted {
+        fn new(size: usize) -> Self {
+            Counted {
+                size: Cell::new(size),
+                calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl DataSize for Counted {
+        const IS_DYNAMIC: bool = true;
+        const STATIC_HEAP_SIZE: usize = 0;
+
+        fn estimate_heap_size(&self) -> usize {
+            self.calls.set(self.calls.get() + 1);
+            self.size.get()
+        }
+    }
+
+    #[test]
+    fn caches_estimate() {
+        let tracked = Tracked::new(Counted::new(42));
+        assert_eq!(tracked.calls.get(), 0);
+
+        // The first call misses, all following ones hit the cache.
+        assert_eq!(data_size(&tracked), 42);
+        assert_eq!(data_size(&tracked), 42);
+        assert_eq!(data_size(&tracked), 42);
+        assert_eq!(tracked.calls.get(), 1);
+    }
+
+    #[test]
+    fn mutable_access_invalidates() {
+        let mut tracked = Tracked::new(Counted::new(42));
+        assert_eq!(data_size(&tracked), 42);
+
+        *tracked.size.get_mut() = 100;
+        assert_eq!(data_size(&tracked), 100);
+        assert_eq!(tracked.calls.get(), 2);
+    }
+
+    #[test]
+    fn explicit_invalidation() {
+        let mut tracked = Tracked::new(Counted::new(42));
+        assert_eq!(data_size(&tracked), 42);
+
+        // Modifications through a shared reference go unnoticed until the cache is invalidated.
+        tracked.size.set(100);
+        assert_eq!(data_size(&tracked), 42);
+
+        tracked.invalidate();
+        assert_eq!(data_size(&tracked), 100);
+        assert_eq!(tracked.calls.get(), 2);
+    }
+
+    #[test]
+    fn limited_estimate_is_not_cached() {
+        let tracked = Tracked::new(vec![vec![0u8; 8]; 4]);
+
+        let (partial, exceeded) = crate::data_size_with_limit(&tracked, 1);
+        assert!(exceeded);
+        assert!(partial < data_size(&*tracked));
+
+        // The full estimate is unaffected by the partial one.
+        assert_eq!(data_size(&tracked), data_size(&*tracked));
+        assert_eq!(
+            crate::data_size_with_limit(&tracked, 1),
+            (data_size(&*tracked), false)
+        );
+    }
+
+    #[test]
+    fn sizes_like_inner_value() {
+        let tracked = Tracked::new(vec![1u64, 2, 3]);
+
+        assert!(Tracked::<Vec<u64>>::IS_DYNAMIC);
+        assert_eq!(data_size(&tracked), data_size(&*tracked));
+    }
+}
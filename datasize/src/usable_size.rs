@@ -0,0 +1,193 @@
+//! Querying the usable size of heap allocations from the system allocator.
+//!
+//! Allocators round up the requested size of an allocation, the additional bytes are usable but
+//! were never asked for. On platforms whose `malloc` can report this usable size, it is used instead
+//! of the requested size. Everywhere else, the requested size is reported as before.
+//!
+//! The query assumes that the global allocator is the system `malloc`, or a replacement exporting
+//! the same symbols (e.g. jemalloc built with unprefixed symbols). Pointers handed out by any other
+//! global allocator must not be queried, since the system allocator does not know them. As the
+//! crate cannot verify this, allocators are only queried within calls to the unsafe
+//! `data_size_usable`, whose caller guarantees it. All other estimates use the requested size.
+
+use core::cell::Cell;
+use core::ffi::c_void;
+
+use crate::DataSize;
+
+std::thread_local! {
+    /// The number of calls to `data_size_usable` in progress on the current thread.
+    static ACTIVE: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Estimates the heap size of `value` like `data_size`, using the usable size of its allocations.
+///
+/// Every allocation of a `Box`, `Vec` or `String` is queried for its usable size, i.e. the
+/// requested size including the rounding of the allocator. On platforms without support, the
+/// requested size is used.
+///
+/// # Safety
+///
+/// The global allocator must be the system `malloc`, or a replacement exporting the same symbols,
+/// such as jemalloc with unprefixed symbols. Querying allocations made by any other allocator,
+/// e.g. one installed through `#[global_allocator]`, is undefined behavior.
+pub unsafe fn data_size_usable<T>(value: &T) -> usize
+where
+    T: DataSize + ?Sized,
+{
+    /// Ends the query when dropped, even if estimating panics.
+    struct Guard;
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            ACTIVE.with(|active| active.set(active.get() - 1));
+        }
+    }
+
+    ACTIVE.with(|active| active.set(active.get() + 1));
+    let _guard = Guard;
+    crate::data_size(value)
+}
+
+/// Returns whether allocations may be queried, i.e. whether `data_size_usable` is in progress.
+#[inline]
+pub(crate) fn enabled() -> bool {
+    ACTIVE.with(|active| active.get() > 0)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+extern "C" {
+    fn malloc_usable_size(ptr: *mut c_void) -> usize;
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+extern "C" {
+    fn malloc_size(ptr: *const c_void) -> usize;
+}
+
+/// Returns the usable size of the allocation at `ptr`, made for `requested` bytes.
+///
+/// The result is never smaller than `requested`. Empty allocations are not allocated at all, so
+/// their `ptr` is never queried.
+///
+/// Outside of `data_size_usable`, the allocator is not queried and `requested` is returned.
+///
+/// # Safety
+///
+/// Unless `requested` is zero, `ptr` must point to the start of a live allocation of the global
+/// allocator.
+#[inline]
+pub(crate) unsafe fn usable_size(ptr: *const u8, requested: usize) -> usize {
+    if requested == 0 || !enabled() {
+        return requested;
+    }
+
+    requested.max(query(ptr, requested))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+#[inline]
+unsafe fn query(ptr: *const u8, _requested: usize) -> usize {
+    malloc_usable_size(ptr as *mut c_void)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[inline]
+unsafe fn query(ptr: *const u8, _requested: usize) -> usize {
+    malloc_size(ptr as *const c_void)
+}
+
+// Other platforms, notably Windows, where the system allocator of the standard library is not
+// `malloc` based, fall back to the requested size.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "ios"
+)))]
+#[inline]
+unsafe fn query(_ptr: *const u8, requested: usize) -> usize {
+    requested
+}
+
+#[cfg(test)]
+mod tests {
+    use super::data_size_usable;
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    // SAFETY (all tests): The tests use the default global allocator, the system `malloc`.
+
+    #[test]
+    fn usable_size_box() {
+        assert!(!Box::<u64>::IS_DYNAMIC);
+        assert!(unsafe { data_size_usable(&Box::new(1u64)) } >= 8);
+        assert!(unsafe { data_size_usable(&Box::new([0u8; 100])) } >= 100);
+
+        // Zero-sized values are not allocated, so there is nothing to query.
+        assert_eq!(unsafe { data_size_usable(&Box::new(())) }, 0);
+    }
+
+    #[test]
+    fn usable_size_boxed_str() {
+        let value: Box<str> = String::from("hello").into_boxed_str();
+        assert!(unsafe { data_size_usable(&value) } >= 5);
+    }
+
+    #[test]
+    fn usable_size_boxed_dyn_data_size() {
+        // A boxed trait object is queried like the box of its concrete value.
+        let value: Box<dyn crate::DynDataSize> = Box::new([0u8; 5]);
+        assert_eq!(unsafe { data_size_usable(&value) }, unsafe {
+            data_size_usable(&Box::new([0u8; 5]))
+        });
+    }
+
+    #[test]
+    fn usable_size_of_non_dynamic_elements() {
+        // Boxes are not dynamic, but still queried individually.
+        let value = vec![Box::new(1u8), Box::new(2u8)];
+        let boxes: usize = value
+            .iter()
+            .map(|boxed| unsafe { data_size_usable(boxed) })
+            .sum();
+        let buffer = data_size(&value) - 2 * data_size(&Box::new(1u8));
+        assert!(unsafe { data_size_usable(&value) } >= buffer + boxes);
+    }
+
+    #[test]
+    fn usable_size_vec() {
+        let mut value: Vec<u8> = Vec::with_capacity(13);
+        value.push(1);
+        assert!(unsafe { data_size_usable(&value) } >= data_size(&value));
+
+        let nested = vec![vec![0u32; 3]; 2];
+        assert!(unsafe { data_size_usable(&nested) } >= 2 * 24 + 2 * 12);
+
+        assert_eq!(unsafe { data_size_usable(&Vec::<u64>::new()) }, 0);
+    }
+
+    #[test]
+    fn usable_size_string() {
+        let mut value = String::with_capacity(13);
+        value.push('a');
+        assert!(unsafe { data_size_usable(&value) } >= data_size(&value));
+        let size = unsafe { data_size_usable(&value) };
+        assert_eq!(size, unsafe { data_size_usable(&value.into_bytes()) });
+
+        assert_eq!(unsafe { data_size_usable(&String::new()) }, 0);
+    }
+
+    #[test]
+    fn usable_size_is_opt_in() {
+        // Safe estimates never query the allocator.
+        let value = vec![0u8; 13];
+        let size = 13 + allocation_overhead(13);
+        assert_eq!(data_size(&value), size);
+        assert_eq!(data_size(&Box::new(1u8)), 1 + allocation_overhead(1));
+
+        // The query ends with the call.
+        let _ = unsafe { data_size_usable(&value) };
+        assert_eq!(data_size(&value), size);
+    }
+}
@@ -0,0 +1,20 @@
+use super::{non_dynamic_const_heap_size, DataSize};
+
+non_dynamic_const_heap_size!(uuid::Uuid, 0);
+
+#[cfg(test)]
+mod tests {
+    use crate::{allocation_overhead, data_size, DataSize};
+
+    #[test]
+    fn test_uuid() {
+        let ids: Vec<uuid::Uuid> = (0..4u128).map(uuid::Uuid::from_u128).collect();
+
+        assert!(!uuid::Uuid::IS_DYNAMIC);
+        assert_eq!(data_size(&ids[0]), 0);
+        assert_eq!(
+            data_size(&ids),
+            ids.len() * 16 + allocation_overhead(ids.len() * 16)
+        );
+    }
+}
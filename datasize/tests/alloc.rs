@@ -0,0 +1,118 @@
+//! Checks that the `alloc` based implementations are available without `std`.
+//!
+//! Run with `cargo test --no-default-features --features alloc --test alloc`.
+
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+use datasize::{allocation_overhead, data_size, DataSize};
+
+#[derive(DataSize)]
+struct Message {
+    payload: Vec<u8>,
+    sender: String,
+    header: Box<u64>,
+}
+
+#[test]
+fn alloc_types_are_sized() {
+    let message = Message {
+        payload: vec![0; 16],
+        sender: String::from("node"),
+        header: Box::new(1),
+    };
+    assert_eq!(
+        data_size(&message),
+        16 + allocation_overhead(16) + 4 + allocation_overhead(4) + 8 + allocation_overhead(8)
+    );
+
+    let queue: VecDeque<u32> = (0..4).collect();
+    assert!(data_size(&queue) >= 16);
+
+    let mut map: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+    map.insert(1, vec![0; 8]);
+    assert!(data_size(&map) >= 8);
+}
+
+#[derive(DataSize)]
+struct Index {
+    names: BTreeSet<String>,
+    lengths: BTreeMap<u32, u32>,
+}
+
+#[test]
+fn btree_collections_are_sized() {
+    assert_eq!(data_size(&BTreeSet::<String>::new()), 0);
+    assert_eq!(data_size(&BTreeMap::<u32, u32>::new()), 0);
+
+    let mut index = Index {
+        names: BTreeSet::new(),
+        lengths: BTreeMap::new(),
+    };
+    index.names.insert(String::from("first"));
+    index.lengths.insert(1, 5);
+
+    assert!(data_size(&index.lengths) >= 8);
+    assert!(data_size(&index.names) >= 5);
+    assert_eq!(
+        data_size(&index),
+        data_size(&index.names) + data_size(&index.lengths)
+    );
+
+    // Owned strings are counted on top of the nodes.
+    let owned: BTreeMap<u32, String> =
+        vec![(1, String::from("first")), (2, String::from("second"))]
+            .into_iter()
+            .collect();
+    let empty: BTreeMap<u32, String> = vec![(1, String::new()), (2, String::new())]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        data_size(&owned),
+        data_size(&empty) + 5 + allocation_overhead(5) + 6 + allocation_overhead(6)
+    );
+}
+
+#[test]
+fn vec_deque_is_sized() {
+    let mut queue: VecDeque<String> = VecDeque::with_capacity(4);
+    assert_eq!(data_size(&VecDeque::<String>::new()), 0);
+
+    // Filled up to its capacity from both ends, so the buffer wraps around.
+    while queue.len() < queue.capacity() {
+        queue.push_back(String::from("ab"));
+        queue.push_front(String::from("c"));
+    }
+    let strings: usize = queue
+        .iter()
+        .map(|string| string.capacity() + allocation_overhead(string.capacity()))
+        .sum();
+    let buffer = queue.capacity() * core::mem::size_of::<String>();
+
+    assert_eq!(
+        data_size(&queue),
+        buffer + allocation_overhead(buffer) + strings
+    );
+}
+
+#[test]
+fn cow_is_sized() {
+    let borrowed: Cow<'static, str> = Cow::Borrowed("hello");
+    let owned: Cow<'static, str> = Cow::Owned(String::from("hello"));
+
+    assert_eq!(data_size(&borrowed), 0);
+    assert_eq!(data_size(&owned), 5 + allocation_overhead(5));
+
+    let slice: Cow<'static, [u32]> = Cow::Owned(vec![1, 2, 3, 4]);
+    assert_eq!(data_size(&slice), 16 + allocation_overhead(16));
+}
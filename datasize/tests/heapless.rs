@@ -0,0 +1,63 @@
+//! Checks the `heapless` implementations, which are meant to be used without `std`.
+//!
+//! Run with `cargo test --no-default-features --features heapless-types --test heapless`.
+
+#![cfg(feature = "heapless-types")]
+#![allow(clippy::assertions_on_constants)]
+
+use datasize::{data_size, DataSize};
+
+#[derive(DataSize)]
+struct Frame {
+    payload: heapless::Vec<u8, 16>,
+    name: heapless::String<8>,
+}
+
+#[test]
+fn heapless_vec_has_no_heap() {
+    let mut payload: heapless::Vec<u8, 16> = heapless::Vec::new();
+    payload.extend_from_slice(&[1, 2, 3, 4]).unwrap();
+
+    assert!(!heapless::Vec::<u8, 16>::IS_DYNAMIC);
+    assert_eq!(heapless::Vec::<u8, 16>::STATIC_HEAP_SIZE, 0);
+    assert_eq!(data_size(&payload), 0);
+
+    let mut name = heapless::String::new();
+    name.push_str("frame").unwrap();
+
+    let frame = Frame { payload, name };
+    assert!(!Frame::IS_DYNAMIC);
+    assert_eq!(data_size(&frame), 0);
+}
+
+/// Stands in for a handle to heap data, which is not available without `std` or `alloc`.
+struct Handle(usize);
+
+impl DataSize for Handle {
+    const IS_DYNAMIC: bool = true;
+    const STATIC_HEAP_SIZE: usize = 0;
+
+    fn estimate_heap_size(&self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn heapless_collections_count_element_heap() {
+    let mut handles: heapless::Deque<Handle, 4> = heapless::Deque::new();
+    handles.push_back(Handle(16)).ok().unwrap();
+    handles.push_back(Handle(8)).ok().unwrap();
+    assert!(heapless::Deque::<Handle, 4>::IS_DYNAMIC);
+    assert_eq!(data_size(&handles), 24);
+
+    let mut map: heapless::FnvIndexMap<u8, Handle, 4> = heapless::FnvIndexMap::new();
+    map.insert(1, Handle(8)).ok().unwrap();
+    map.insert(2, Handle(4)).ok().unwrap();
+    assert_eq!(data_size(&map), 12);
+    assert_eq!(datasize::data_size_with_limit(&map, 1), (8, true));
+
+    let mut set: heapless::FnvIndexSet<u8, 4> = heapless::FnvIndexSet::new();
+    set.insert(1).unwrap();
+    assert!(!heapless::FnvIndexSet::<u8, 4>::IS_DYNAMIC);
+    assert_eq!(data_size(&set), 0);
+}
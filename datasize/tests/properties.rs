@@ -0,0 +1,124 @@
+//! Property tests for the collection implementations.
+//!
+//! Checks that estimates never shrink when inserting elements and match the expected values for
+//! elements without heap data.
+
+#![cfg(feature = "std")]
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::size_of,
+};
+
+use datasize::{allocation_overhead, data_size};
+use proptest::prelude::*;
+
+/// Returns the expected size of a buffer of `len` elements allocated for `capacity` elements.
+fn expected_buffer(len: usize, capacity: usize, element_size: usize) -> usize {
+    let counted = if cfg!(feature = "vec-len-sizing") {
+        len
+    } else {
+        capacity
+    };
+    counted * element_size + allocation_overhead(capacity * element_size)
+}
+
+/// Returns the expected size of the table of a `HashMap` of `capacity` entries of `entry_size`.
+fn expected_table(capacity: usize, entry_size: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+
+    let buckets = if capacity < 4 {
+        4
+    } else if capacity < 8 {
+        8
+    } else {
+        (capacity * 8 / 7).next_power_of_two()
+    };
+    let table = buckets * (entry_size + 1);
+    table + allocation_overhead(table)
+}
+
+proptest! {
+    #[test]
+    fn vec_matches_buffer(items in prop::collection::vec(any::<u64>(), 0..200)) {
+        let mut vec = Vec::new();
+        let mut previous = data_size(&vec);
+        prop_assert_eq!(previous, 0);
+
+        for item in items {
+            vec.push(item);
+
+            let size = data_size(&vec);
+            prop_assert!(size >= previous);
+            prop_assert_eq!(size, expected_buffer(vec.len(), vec.capacity(), size_of::<u64>()));
+            previous = size;
+        }
+    }
+
+    #[test]
+    fn vec_of_dynamic_is_monotonic(items in prop::collection::vec(".{0,20}", 0..50)) {
+        let mut vec: Vec<String> = Vec::new();
+        let mut previous = data_size(&vec);
+
+        for item in items {
+            let item_size = data_size(&item);
+            vec.push(item);
+
+            let size = data_size(&vec);
+            prop_assert!(size >= previous + item_size);
+            previous = size;
+        }
+    }
+
+    #[test]
+    fn string_matches_buffer(chunks in prop::collection::vec(".{0,20}", 0..50)) {
+        let mut string = String::new();
+        let mut previous = data_size(&string);
+        prop_assert_eq!(previous, 0);
+
+        for chunk in chunks {
+            string.push_str(&chunk);
+
+            let size = data_size(&string);
+            prop_assert!(size >= previous);
+            prop_assert_eq!(size, expected_buffer(string.len(), string.capacity(), 1));
+            previous = size;
+        }
+    }
+
+    #[test]
+    fn hash_map_matches_table(entries in prop::collection::vec(any::<(u32, u64)>(), 0..200)) {
+        let mut map = HashMap::new();
+        let mut previous = data_size(&map);
+        prop_assert_eq!(previous, 0);
+
+        for (key, value) in entries {
+            map.insert(key, value);
+
+            let size = data_size(&map);
+            prop_assert!(size >= previous);
+            prop_assert_eq!(size, expected_table(map.capacity(), size_of::<(u32, u64)>()));
+            prop_assert!(size >= map.len() * size_of::<(u32, u64)>());
+            previous = size;
+        }
+    }
+
+    #[test]
+    fn btree_map_is_monotonic(entries in prop::collection::vec(any::<(u32, u64)>(), 0..200)) {
+        let mut map = BTreeMap::new();
+        let mut previous = data_size(&map);
+        prop_assert_eq!(previous, 0);
+
+        for (key, value) in entries {
+            map.insert(key, value);
+
+            // Every entry is stored in one of the nodes, so they are never undercounted.
+            let size = data_size(&map);
+            prop_assert!(size >= previous);
+            prop_assert!(size >= map.len() * (size_of::<u32>() + size_of::<u64>()));
+            previous = size;
+        }
+    }
+}
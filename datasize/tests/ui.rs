@@ -0,0 +1,11 @@
+//! Compile tests for the `DataSize` derive macro.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+
+    #[cfg(feature = "strict-shared")]
+    t.compile_fail("tests/ui/strict_shared/*.rs");
+}
@@ -0,0 +1,11 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+struct Boxed {
+    value: Box<u64>,
+}
+
+datasize::assert_static_heap_size!(Boxed, 8 + datasize::allocation_overhead(8));
+datasize::assert_static_heap_size!(Boxed, 16);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+#[data_size(bound(U))]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+#[data_size(const = 0)]
+#[data_size(is_dynamic = true)]
+struct Empty {
+    value: u64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+#[data_size(skip_bound(U))]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
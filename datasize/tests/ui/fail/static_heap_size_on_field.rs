@@ -0,0 +1,9 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+struct Foo {
+    #[data_size(static_heap_size = 8)]
+    value: Box<u64>,
+}
+
+fn main() {}
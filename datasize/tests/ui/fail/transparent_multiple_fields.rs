@@ -0,0 +1,10 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+#[data_size(transparent)]
+struct Pair {
+    first: Vec<u8>,
+    second: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+#[data_size(transparent)]
+enum Either {
+    Left(Vec<u8>),
+    Right(String),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+union Bits {
+    int: u64,
+    float: f64,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+#[data_size(unit_enum)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use datasize::DataSize;
+
+#[derive(DataSize)]
+#[data_size(unit_enum)]
+enum Shape {
+    Empty,
+    Polygon(Vec<(f32, f32)>),
+}
+
+fn main() {}
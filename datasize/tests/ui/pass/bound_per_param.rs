@@ -0,0 +1,48 @@
+#![deny(warnings)]
+
+use core::marker::PhantomData;
+
+use datasize::{allocation_overhead, data_size, DataSize};
+
+#[derive(DataSize)]
+struct Values<T> {
+    items: Vec<T>,
+}
+
+/// Not implementing `DataSize`, only used as a marker.
+pub struct Meters;
+
+// Without `bound`, the private `Values<T>` would leak into the bounds of the public impl.
+#[derive(DataSize)]
+#[data_size(bound(T))]
+pub struct Measurements<T, U> {
+    values: Values<T>,
+    unit: PhantomData<U>,
+}
+
+#[derive(DataSize)]
+#[data_size(bound(T))]
+pub enum Reading<T, U> {
+    Single(Box<T>),
+    Missing(PhantomData<U>),
+}
+
+fn assert_data_size<T: DataSize>() {}
+
+fn main() {
+    assert_data_size::<Measurements<u32, Meters>>();
+    assert_data_size::<Reading<u32, Meters>>();
+
+    let measurements: Measurements<u32, Meters> = Measurements {
+        values: Values {
+            items: vec![1, 2, 3],
+        },
+        unit: PhantomData,
+    };
+    assert_eq!(data_size(&measurements), 12 + allocation_overhead(12));
+    assert_eq!(
+        data_size(&Reading::<u64, Meters>::Single(Box::new(1))),
+        8 + allocation_overhead(8)
+    );
+
+}
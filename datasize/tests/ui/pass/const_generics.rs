@@ -0,0 +1,68 @@
+use datasize::{allocation_overhead, data_size, DataSize};
+
+#[derive(DataSize)]
+struct Ring<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+}
+
+#[derive(DataSize)]
+struct Slots<T, const N: usize> {
+    slots: [Option<T>; N],
+}
+
+#[derive(DataSize)]
+struct Chunk<const N: usize>([u16; N], Vec<u8>);
+
+// Inline bounds are kept in the generated impl.
+#[derive(DataSize)]
+struct Window<T: Copy, const N: usize> {
+    values: [T; N],
+    history: Vec<T>,
+}
+
+#[derive(DataSize)]
+enum Buffer<T, const N: usize> {
+    Inline([T; N]),
+    Spilled { items: Vec<T>, min: [u8; N] },
+    Empty,
+}
+
+fn main() {
+    assert!(!Ring::<16>::IS_DYNAMIC);
+    assert_eq!(
+        data_size(&Ring {
+            buf: [0; 16],
+            head: 0
+        }),
+        0
+    );
+
+    assert!(Slots::<Box<u64>, 4>::IS_DYNAMIC);
+    let slots: Slots<Box<u64>, 4> = Slots {
+        slots: [Some(Box::new(1)), None, Some(Box::new(2)), None],
+    };
+    assert_eq!(data_size(&slots), 2 * (8 + allocation_overhead(8)));
+
+    assert_eq!(
+        data_size(&Chunk([0; 8], vec![1, 2, 3])),
+        3 + allocation_overhead(3)
+    );
+
+    let window = Window {
+        values: [1u32; 4],
+        history: vec![2, 3],
+    };
+    assert_eq!(data_size(&window), 8 + allocation_overhead(8));
+
+    let inline: Buffer<Box<u8>, 2> = Buffer::Inline([Box::new(1), Box::new(2)]);
+    assert_eq!(data_size(&inline), 2 * (1 + allocation_overhead(1)));
+
+    let spilled: Buffer<u32, 2> = Buffer::Spilled {
+        items: vec![1, 2, 3],
+        min: [0; 2],
+    };
+    assert_eq!(data_size(&spilled), 12 + allocation_overhead(12));
+
+    assert_eq!(data_size(&Buffer::<u32, 2>::Empty), 0);
+}
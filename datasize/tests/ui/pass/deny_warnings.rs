@@ -0,0 +1,96 @@
+#![deny(warnings)]
+
+use std::marker::PhantomData;
+
+use datasize::{allocation_overhead, data_size, DataSize};
+
+fn estimate_name(value: &String) -> usize {
+    value.capacity()
+}
+
+#[derive(DataSize)]
+struct Empty;
+
+#[derive(DataSize)]
+struct Unit();
+
+#[derive(DataSize)]
+struct Private<T, U> {
+    value: T,
+    #[data_size(skip)]
+    _marker: PhantomData<U>,
+    #[data_size(with = estimate_name)]
+    name: String,
+}
+
+#[derive(DataSize)]
+struct Repeated<T> {
+    first: T,
+    second: T,
+    third: Option<T>,
+}
+
+#[derive(DataSize)]
+struct Tuple<T>(T, #[data_size(skip)] u8);
+
+#[derive(DataSize)]
+enum NoVariants {}
+
+#[derive(DataSize)]
+enum Mixed<T> {
+    Unit,
+    Named { value: T, #[data_size(skip)] ignored: u32 },
+    Unnamed(Box<u64>, #[data_size(skip)] u8),
+    #[data_size(skip)]
+    Skipped(Vec<u8>),
+}
+
+#[derive(DataSize)]
+#[data_size(const = 0)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+#[derive(DataSize)]
+#[data_size(static_heap_size = 8)]
+struct Overridden {
+    value: Box<u64>,
+}
+
+fn main() {
+    let private: Private<Vec<u8>, ()> = Private {
+        value: vec![1, 2, 3],
+        _marker: PhantomData,
+        name: String::with_capacity(4),
+    };
+
+    let total = data_size(&Empty)
+        + data_size(&Unit())
+        + data_size(&private)
+        + data_size(&Tuple(1u64, 2))
+        + data_size(&Repeated { first: 1u8, second: 2, third: None })
+        + data_size(&Mixed::<u8>::Unit)
+        + data_size(&Mixed::<u8>::Named { value: 1, ignored: 2 })
+        + data_size(&Mixed::<u8>::Unnamed(Box::new(1), 2))
+        + data_size(&Mixed::<u8>::Skipped(Vec::new()))
+        + data_size(&Bits { int: 1 })
+        + data_size(&Overridden { value: Box::new(1) });
+
+    assert_eq!(
+        total,
+        3 + allocation_overhead(3) + 4 + 2 * (8 + allocation_overhead(8))
+    );
+
+    let _ = NoVariants::IS_DYNAMIC;
+
+    // Read all skipped fields, which would otherwise trigger `dead_code` on their own.
+    let _ = Tuple(1u8, 2).1;
+    if let Mixed::<u8>::Named { ignored, .. } = (Mixed::Named { value: 1, ignored: 2 }) {
+        assert_eq!(ignored, 2);
+    }
+    if let Mixed::<u8>::Skipped(items) = Mixed::Skipped(Vec::new()) {
+        assert!(items.is_empty());
+    }
+    assert_eq!(unsafe { Bits { int: 0 }.float }.to_bits(), 0);
+}
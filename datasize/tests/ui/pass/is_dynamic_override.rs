@@ -0,0 +1,52 @@
+use datasize::{allocation_overhead, data_size, DataSize};
+
+// By invariant, `pending` is always empty whenever the registry is sized.
+#[derive(DataSize)]
+#[data_size(is_dynamic = false)]
+struct Registry {
+    pending: Vec<u32>,
+    id: Box<u64>,
+}
+
+// Forcing a dynamic estimate on a type that would otherwise be static.
+#[derive(DataSize)]
+#[data_size(is_dynamic = true)]
+struct Fixed {
+    value: Box<u32>,
+}
+
+#[derive(DataSize)]
+#[data_size(is_dynamic = false)]
+#[data_size(static_heap_size = 8)]
+enum Slot {
+    Full(Box<u64>),
+    Reserved(Box<[u8; 8]>),
+}
+
+fn main() {
+    assert!(!Registry::IS_DYNAMIC);
+    assert_eq!(Registry::STATIC_HEAP_SIZE, 8 + allocation_overhead(8));
+
+    let registry = Registry {
+        pending: Vec::new(),
+        id: Box::new(1),
+    };
+    assert_eq!(data_size(&registry), 8 + allocation_overhead(8));
+    // The derived estimate is still available.
+    assert_eq!(registry.estimate_heap_size(), 8 + allocation_overhead(8));
+
+    assert!(Fixed::IS_DYNAMIC);
+    assert_eq!(
+        data_size(&Fixed { value: Box::new(1) }),
+        4 + allocation_overhead(4)
+    );
+
+
+    assert!(!Slot::IS_DYNAMIC);
+    assert_eq!(Slot::STATIC_HEAP_SIZE, 8);
+    assert_eq!(
+        data_size(&Slot::Full(Box::new(1))),
+        8 + allocation_overhead(8)
+    );
+
+}
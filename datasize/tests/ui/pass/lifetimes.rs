@@ -0,0 +1,55 @@
+#![deny(warnings)]
+
+use datasize::{allocation_overhead, data_size, DataSize};
+
+#[derive(DataSize)]
+struct View<'a> {
+    data: &'a [u8],
+    owned: Vec<u8>,
+}
+
+#[derive(DataSize)]
+struct Split<'a, 'b: 'a, T> {
+    head: &'a [T],
+    tail: &'b str,
+    spare: Vec<T>,
+}
+
+#[derive(DataSize)]
+enum Token<'a> {
+    Borrowed(&'a str),
+    Owned(String),
+    Pair { key: &'a str, value: Box<u32> },
+}
+
+fn main() {
+    let bytes = vec![0u8; 64];
+    let view = View {
+        data: &bytes,
+        owned: vec![1, 2, 3],
+    };
+    // Only the owned field is counted.
+    assert_eq!(data_size(&view), 3 + allocation_overhead(3));
+
+    let items = [1u16, 2, 3];
+    let split = Split {
+        head: &items,
+        tail: "borrowed",
+        spare: vec![4u16],
+    };
+    assert_eq!(data_size(&split), 2 + allocation_overhead(2));
+
+    assert_eq!(data_size(&Token::Borrowed("borrowed")), 0);
+    assert_eq!(
+        data_size(&Token::Owned("owned".to_owned())),
+        5 + allocation_overhead(5)
+    );
+    assert_eq!(
+        data_size(&Token::Pair {
+            key: "key",
+            value: Box::new(1)
+        }),
+        4 + allocation_overhead(4)
+    );
+
+}
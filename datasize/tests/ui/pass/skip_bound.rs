@@ -0,0 +1,49 @@
+#![deny(warnings)]
+
+use core::marker::PhantomData;
+
+use datasize::{allocation_overhead, data_size, DataSize};
+
+/// Not implementing `DataSize`.
+#[derive(Default)]
+struct Handle;
+
+#[derive(DataSize)]
+#[data_size(skip_bound(T, K))]
+struct View<'a, T, K> {
+    items: Vec<u32>,
+    current: &'a T,
+    key: K,
+    kind: PhantomData<T>,
+}
+
+#[derive(DataSize)]
+#[data_size(skip_bound(K))]
+enum Slot<K, V> {
+    Empty(K),
+    Full { key: K, value: Vec<V> },
+}
+
+fn assert_data_size<T: DataSize>() {}
+
+fn main() {
+    assert_data_size::<View<Handle, Handle>>();
+    assert_data_size::<Slot<Handle, u8>>();
+
+    let handle = Handle;
+    let view = View {
+        items: vec![1, 2],
+        current: &handle,
+        key: Handle,
+        kind: PhantomData,
+    };
+    assert_eq!(data_size(&view), 8 + allocation_overhead(8));
+
+    assert_eq!(data_size(&Slot::<Handle, u8>::Empty(Handle)), 0);
+    let full = Slot::Full {
+        key: Handle,
+        value: vec![1u8, 2, 3],
+    };
+    assert_eq!(data_size(&full), 3 + allocation_overhead(3));
+
+}
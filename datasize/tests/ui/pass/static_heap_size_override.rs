@@ -0,0 +1,41 @@
+use datasize::{allocation_overhead, data_size, DataSize};
+
+// The derived lower bound would be 0, as `Option<Box<u64>>` may be `None`.
+#[derive(DataSize)]
+#[data_size(static_heap_size = 8)]
+struct AlwaysBoxed {
+    value: Option<Box<u64>>,
+    items: Vec<u32>,
+}
+
+#[derive(DataSize)]
+#[data_size(static_heap_size = 2 * 4)]
+enum Either {
+    Left(Box<u64>),
+    Right(Box<[u8; 8]>),
+}
+
+fn main() {
+    assert_eq!(AlwaysBoxed::STATIC_HEAP_SIZE, 8);
+    assert!(AlwaysBoxed::IS_DYNAMIC);
+
+    let value = AlwaysBoxed {
+        value: Some(Box::new(1)),
+        items: vec![1, 2, 3],
+    };
+    assert_eq!(
+        data_size(&value),
+        8 + allocation_overhead(8) + data_size(&value.items)
+    );
+
+    assert_eq!(Either::STATIC_HEAP_SIZE, 8);
+    assert_eq!(
+        data_size(&Either::Left(Box::new(1))),
+        8 + allocation_overhead(8)
+    );
+    assert_eq!(
+        data_size(&Either::Right(Box::new([0; 8]))),
+        8 + allocation_overhead(8)
+    );
+
+}
@@ -0,0 +1,64 @@
+#![deny(warnings)]
+
+use core::marker::PhantomData;
+
+use datasize::{allocation_overhead, data_size, DataSize};
+
+#[derive(DataSize)]
+#[data_size(transparent)]
+struct UserId(Box<u64>);
+
+#[derive(DataSize)]
+#[data_size(transparent)]
+struct Name {
+    value: String,
+}
+
+#[derive(DataSize)]
+#[data_size(transparent)]
+struct Tagged<T, K> {
+    items: Vec<T>,
+    #[data_size(skip)]
+    cache: Vec<u8>,
+    #[data_size(skip)]
+    kind: PhantomData<K>,
+}
+
+#[derive(DataSize)]
+#[data_size(transparent)]
+#[data_size(skip_bound(K))]
+struct Keyed<K> {
+    key: K,
+    value: Vec<u8>,
+}
+
+/// Not implementing `DataSize`.
+struct Handle;
+
+fn main() {
+    assert!(!UserId::IS_DYNAMIC);
+    assert_eq!(UserId::STATIC_HEAP_SIZE, 8 + allocation_overhead(8));
+    assert_eq!(data_size(&UserId(Box::new(1))), 8 + allocation_overhead(8));
+
+    assert!(Name::IS_DYNAMIC);
+    assert_eq!(
+        data_size(&Name {
+            value: "name".to_owned()
+        }),
+        4 + allocation_overhead(4)
+    );
+
+    let tagged: Tagged<u32, ()> = Tagged {
+        items: vec![1, 2],
+        cache: vec![0; 100],
+        kind: PhantomData,
+    };
+    assert_eq!(data_size(&tagged), 8 + allocation_overhead(8));
+
+    let keyed = Keyed {
+        key: Handle,
+        value: vec![0; 3],
+    };
+    assert_eq!(data_size(&keyed), 3 + allocation_overhead(3));
+
+}
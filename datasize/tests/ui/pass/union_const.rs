@@ -0,0 +1,15 @@
+use datasize::{data_size, DataSize};
+
+#[derive(DataSize)]
+#[data_size(const = 16)]
+union Bits {
+    int: u64,
+    float: f64,
+}
+
+fn main() {
+    assert!(!Bits::IS_DYNAMIC);
+    assert_eq!(Bits::STATIC_HEAP_SIZE, 16);
+    assert_eq!(data_size(&Bits { int: 1 }), 16);
+    assert_eq!(data_size(&Bits { float: 1.0 }), 16);
+}
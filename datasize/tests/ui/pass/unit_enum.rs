@@ -0,0 +1,23 @@
+use datasize::{data_size, DataSize};
+
+#[derive(DataSize)]
+#[data_size(unit_enum)]
+enum Color {
+    Red,
+    Green,
+    Blue = 10,
+}
+
+#[derive(DataSize)]
+#[data_size(unit_enum)]
+enum Never {}
+
+fn main() {
+    assert!(!Color::IS_DYNAMIC);
+    assert_eq!(Color::STATIC_HEAP_SIZE, 0);
+    assert_eq!(data_size(&Color::Red), 0);
+    assert_eq!(data_size(&Color::Green), 0);
+    assert_eq!(data_size(&Color::Blue), 0);
+
+    assert!(!Never::IS_DYNAMIC);
+}
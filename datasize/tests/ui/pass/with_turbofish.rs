@@ -0,0 +1,38 @@
+use datasize::{data_size, DataSize};
+
+mod helpers {
+    /// A generic estimate, assuming every item to be 64 bytes in size.
+    pub fn estimate<T>(value: &Vec<T>) -> usize {
+        value.len() * 64
+    }
+
+    pub struct Weighted;
+
+    impl Weighted {
+        pub fn estimate<T, const N: usize>(value: &Vec<T>) -> usize {
+            value.len() * N
+        }
+    }
+}
+
+struct Foo;
+
+#[derive(DataSize)]
+struct Container {
+    #[data_size(with = helpers::estimate::<Foo>)]
+    items: Vec<Foo>,
+    #[data_size(with = helpers::Weighted::estimate::<Foo, 16>)]
+    weighted: Vec<Foo>,
+    #[data_size(with = <helpers::Weighted>::estimate::<Foo, 8>)]
+    qualified: Vec<Foo>,
+}
+
+fn main() {
+    let container = Container {
+        items: vec![Foo, Foo],
+        weighted: vec![Foo],
+        qualified: vec![Foo, Foo, Foo],
+    };
+
+    assert_eq!(data_size(&container), 2 * 64 + 16 + 3 * 8);
+}
@@ -0,0 +1,10 @@
+use std::sync::Arc;
+
+use datasize::DataSize;
+
+#[derive(DataSize)]
+struct Cache {
+    entries: Arc<Vec<u64>>,
+}
+
+fn main() {}
@@ -4,25 +4,94 @@ use proc_macro::TokenStream;
 use quote::quote;
 use std::collections::HashSet;
 use syn::{
-    parse, parse_macro_input, AngleBracketedGenericArguments, AttrStyle, Attribute, Binding,
-    DataEnum, DataStruct, DeriveInput, Generics, Ident, Index, ParenthesizedGenericArguments, Path,
-    PathArguments, ReturnType, TraitBound, Type, TypeArray, TypeBareFn, TypeGroup, TypeImplTrait,
-    TypeParam, TypeParamBound, TypeParen, TypePath, TypePtr, TypeReference, TypeSlice,
-    TypeTraitObject, TypeTuple, WhereClause,
+    ext::IdentExt, parse, parse_macro_input, AngleBracketedGenericArguments, AttrStyle, Attribute,
+    Binding, DataEnum, DataStruct, DeriveInput, Generics, Ident, Index,
+    ParenthesizedGenericArguments, Path, PathArguments, ReturnType, TraitBound, Type, TypeArray,
+    TypeBareFn, TypeGroup, TypeImplTrait, TypeParam, TypeParamBound, TypeParen, TypePath, TypePtr,
+    TypeReference, TypeSlice, TypeTraitObject, TypeTuple, WhereClause,
 };
 
 /// Automatically derive the `DataSize` trait for a type.
 ///
-/// Supports a single option, `#[data_size(skip)]`. If set on a field, it will be ignored entirely
-/// when deriving the implementation.
+/// Supports the following options:
+///
+/// * `#[data_size(skip)]`: If set on a field, it will be ignored entirely when deriving the
+///   implementation.
+/// * `#[data_size(with = ...)]`: If set on a field, the given function is called to estimate its
+///   heap size instead. Any expression path is accepted, e.g. `helpers::estimate::<Foo>`.
+/// * `#[data_size(detailed_with = ...)]`: If set on a field, the given function is called to create
+///   its detailed estimate, returning a `MemUsageNode`. Only used with the `detailed` feature, the
+///   flat estimate is unaffected and can be overridden by `with`.
+/// * `#[data_size(include_stack)]`: If set on a field, the stack size of its type is added to its
+///   estimate, e.g. for precise accounting of the total footprint of boxed slices.
+/// * `#[data_size(const = ...)]`: If set on the type itself, the given constant is used as the
+///   heap size of every value. Required when deriving for unions, whose contents cannot be
+///   inspected.
+/// * `#[data_size(static_heap_size = ...)]`: If set on the type itself, the given constant is used
+///   as `STATIC_HEAP_SIZE` instead of the derived one. `IS_DYNAMIC` and the estimate itself are
+///   still derived.
+/// * `#[data_size(is_dynamic = ...)]`: If set on the type itself, the given constant is used as
+///   `IS_DYNAMIC` instead of the derived one, while the estimate is still derived. Setting it to
+///   `false` for a type whose heap size does vary leads to undercounting, as only
+///   `STATIC_HEAP_SIZE` is reported for every value without calling the estimate.
+/// * `#[data_size(unit_enum)]`: If set on an enum consisting of unit variants only, a trivial
+///   implementation without any heap size is generated. Fails if any variant has fields.
+/// * `#[data_size(transparent)]`: If set on a struct with exactly one non-skipped field, all items
+///   of the implementation are forwarded to that field, e.g. for newtypes.
+/// * `#[data_size(bound(T, ...))]`: If set on the type itself, only the listed type parameters are
+///   required to implement `DataSize`, instead of the types of all fields containing generics.
+///   `bound()` adds no bounds at all.
+/// * `#[data_size(skip_bound(T, ...))]`: If set on the type itself, the listed type parameters are
+///   assumed to contribute no heap data, e.g. because they are only stored in a `PhantomData` or
+///   behind a reference. No bounds are added for them and fields of exactly these types are
+///   skipped.
 #[proc_macro_derive(DataSize, attributes(data_size))]
 pub fn derive_data_size(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    let container_attrs = ContainerAttributes::parse(&input.attrs);
+
+    if let Some(const_size) = container_attrs.const_size {
+        return derive_const(input.ident, input.generics, const_size);
+    }
+
+    if container_attrs.unit_enum {
+        match input.data {
+            syn::Data::Enum(ref de) => {
+                if let Some(variant) = de
+                    .variants
+                    .iter()
+                    .find(|variant| !matches!(variant.fields, syn::Fields::Unit))
+                {
+                    panic!(
+                        "`unit_enum` requires all variants to be unit variants, but `{}` has fields",
+                        variant.ident
+                    );
+                }
+            }
+            _ => panic!("`unit_enum` is only supported on enums"),
+        }
+
+        return derive_const(input.ident, input.generics, syn::parse_quote!(0));
+    }
+
+    if container_attrs.transparent {
+        return match input.data {
+            syn::Data::Struct(ds) => {
+                derive_transparent(input.ident, input.generics, &container_attrs, ds)
+            }
+            _ => panic!("`transparent` is only supported on structs"),
+        };
+    }
 
     match input.data {
-        syn::Data::Struct(ds) => derive_for_struct(input.ident, input.generics, ds),
-        syn::Data::Enum(de) => derive_for_enum(input.ident, input.generics, de),
-        syn::Data::Union(_) => panic!("unions not supported"),
+        syn::Data::Struct(ds) => {
+            derive_for_struct(input.ident, input.generics, &container_attrs, ds)
+        }
+        syn::Data::Enum(de) => derive_for_enum(input.ident, input.generics, &container_attrs, de),
+        syn::Data::Union(_) => panic!(
+            "unions cannot be inspected, an explicit `#[data_size(const = ...)]` attribute is \
+             required to derive `DataSize` for them"
+        ),
     }
 }
 
@@ -161,67 +230,168 @@ fn param_bound_contains_generic(generics: &Generics, bound: &TypeParamBound) ->
 }
 
 #[derive(Debug)]
-/// A single attribute on top of a datasize'd field.
+/// A single attribute on top of a datasize'd field or type.
 enum DataAttribute {
     /// The `data_size(skip)` attribute.
     Skip,
     /// The `data_size(with = "...")` attribute.
-    With(syn::Path),
+    With(syn::ExprPath),
+    /// The `data_size(detailed_with = "...")` attribute.
+    DetailedWith(syn::ExprPath),
+    /// The `data_size(include_stack)` attribute.
+    IncludeStack,
+    /// The `data_size(const = ...)` attribute.
+    Const(syn::Expr),
+    /// The `data_size(static_heap_size = ...)` attribute.
+    StaticHeapSize(syn::Expr),
+    /// The `data_size(is_dynamic = ...)` attribute.
+    IsDynamic(syn::Expr),
+    /// The `data_size(unit_enum)` attribute.
+    UnitEnum,
+    /// The `data_size(transparent)` attribute.
+    Transparent,
+    /// The `data_size(bound(...))` attribute.
+    Bound(Vec<Ident>),
+    /// The `data_size(skip_bound(...))` attribute.
+    SkipBound(Vec<Ident>),
+}
+
+impl DataAttribute {
+    /// Returns the keyword used to specify the attribute.
+    fn keyword(&self) -> &'static str {
+        match self {
+            DataAttribute::Skip => "skip",
+            DataAttribute::With(_) => "with",
+            DataAttribute::DetailedWith(_) => "detailed_with",
+            DataAttribute::IncludeStack => "include_stack",
+            DataAttribute::Const(_) => "const",
+            DataAttribute::StaticHeapSize(_) => "static_heap_size",
+            DataAttribute::IsDynamic(_) => "is_dynamic",
+            DataAttribute::UnitEnum => "unit_enum",
+            DataAttribute::Transparent => "transparent",
+            DataAttribute::Bound(_) => "bound",
+            DataAttribute::SkipBound(_) => "skip_bound",
+        }
+    }
+}
+
+/// Parses the `=` following an attribute keyword.
+fn parse_eq(input: parse::ParseStream, keyword: &str) -> syn::Result<()> {
+    let punct: proc_macro2::Punct = input.parse().expect("PUNCT??");
+    if punct.as_char() != '=' {
+        return Err(syn::parse::Error::new(
+            input.span(),
+            format!("expected `=` after `{}`", keyword),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses a parenthesized list of type parameters following an attribute keyword.
+fn parse_params(input: parse::ParseStream) -> syn::Result<Vec<Ident>> {
+    let content;
+    syn::parenthesized!(content in input);
+
+    let params: syn::punctuated::Punctuated<Ident, syn::Token![,]> =
+        content.parse_terminated(<Ident as parse::Parse>::parse)?;
+    Ok(params.into_iter().collect())
 }
 
 impl parse::Parse for DataAttribute {
     fn parse(input: parse::ParseStream) -> syn::Result<Self> {
-        let ident = input.parse::<Ident>().expect("IDENT??").to_string();
+        // `Ident::parse_any` is required to accept keywords like `const`.
+        let ident = Ident::parse_any(input).expect("IDENT??").to_string();
 
         match ident.as_str() {
             "skip" => Ok(DataAttribute::Skip),
             "with" => {
-                let punct: proc_macro2::Punct = input.parse().expect("PUNCT??");
-                if punct.as_char() != '=' {
-                    return Err(syn::parse::Error::new(
-                        input.span(),
-                        "expected `=` after `with`",
-                    ));
-                }
+                parse_eq(input, "with")?;
 
-                let path: syn::Path = input.parse()?;
+                // Parsed as an expression path, to allow generic functions called with a turbofish.
+                let path: syn::ExprPath = input.parse()?;
                 Ok(DataAttribute::With(path))
             }
+            "detailed_with" => {
+                parse_eq(input, "detailed_with")?;
+
+                let path: syn::ExprPath = input.parse()?;
+                Ok(DataAttribute::DetailedWith(path))
+            }
+            "include_stack" => Ok(DataAttribute::IncludeStack),
+            "const" => {
+                parse_eq(input, "const")?;
+
+                let expr: syn::Expr = input.parse()?;
+                Ok(DataAttribute::Const(expr))
+            }
+            "static_heap_size" => {
+                parse_eq(input, "static_heap_size")?;
+
+                let expr: syn::Expr = input.parse()?;
+                Ok(DataAttribute::StaticHeapSize(expr))
+            }
+            "is_dynamic" => {
+                parse_eq(input, "is_dynamic")?;
+
+                let expr: syn::Expr = input.parse()?;
+                Ok(DataAttribute::IsDynamic(expr))
+            }
+            "unit_enum" => Ok(DataAttribute::UnitEnum),
+            "transparent" => Ok(DataAttribute::Transparent),
+            "bound" => Ok(DataAttribute::Bound(parse_params(input)?)),
+            "skip_bound" => Ok(DataAttribute::SkipBound(parse_params(input)?)),
             kw => panic!("unsupported attribute keyword: {}", kw),
         }
     }
 }
 
+/// Parses all `data_size` attributes from untyped [`Attribute`]s, ignoring any other attributes.
+fn parse_data_attributes(attrs: &[Attribute]) -> Vec<DataAttribute> {
+    let mut parsed = Vec::new();
+
+    for attr in attrs {
+        if attr.style != AttrStyle::Outer {
+            // We ignore inner attributes.
+            continue;
+        }
+
+        // Ensure it is a `data_size` attribute.
+        if attr.path.segments.len() != 1 || attr.path.segments[0].ident != "data_size" {
+            continue;
+        }
+
+        parsed.push(
+            attr.parse_args()
+                .expect("could not parse datasize attribute"),
+        );
+    }
+
+    parsed
+}
+
 /// A set of attributes on top of a field in a struct.
 #[derive(Debug)]
 struct DataSizeAttributes {
     /// Whether or not to skip the field entirely (`data_size(skip)`).
     pub skip: bool,
     /// A function to call instead of deriving the data size.
-    pub with: Option<syn::Path>,
+    pub with: Option<syn::ExprPath>,
+    /// A function to call instead of deriving the detailed estimate.
+    pub detailed_with: Option<syn::ExprPath>,
+    /// Whether to add the stack size of the field to its estimate (`data_size(include_stack)`).
+    pub include_stack: bool,
 }
 
 impl DataSizeAttributes {
     /// Parses a set of attributes from untyped [`Attribute`]s.
-    fn parse(attrs: &Vec<Attribute>) -> Self {
+    fn parse(attrs: &[Attribute]) -> Self {
         let mut skip = None;
         let mut with = None;
+        let mut detailed_with = None;
+        let mut include_stack = None;
 
-        for attr in attrs {
-            if attr.style != AttrStyle::Outer {
-                // We ignore outer attributes.
-                continue;
-            }
-
-            // Ensure it is a `data_size` attribute.
-            if attr.path.segments.len() != 1 || attr.path.segments[0].ident != "data_size" {
-                continue;
-            }
-
-            let parsed: DataAttribute = attr
-                .parse_args()
-                .expect("could not parse datasize attribute");
-
+        for parsed in parse_data_attributes(attrs) {
             match parsed {
                 DataAttribute::Skip => {
                     if skip.is_some() {
@@ -237,31 +407,289 @@ impl DataSizeAttributes {
                         with = Some(fragment)
                     }
                 }
+                DataAttribute::DetailedWith(fragment) => {
+                    if detailed_with.is_some() {
+                        panic!("duplicated `detailed_with` attribute");
+                    } else {
+                        detailed_with = Some(fragment)
+                    }
+                }
+                DataAttribute::IncludeStack => {
+                    if include_stack.is_some() {
+                        panic!("duplicated `include_stack` attribute");
+                    } else {
+                        include_stack = Some(true);
+                    }
+                }
+                other => panic!(
+                    "`{}` is not supported on fields or variants",
+                    other.keyword()
+                ),
             }
         }
 
         DataSizeAttributes {
             skip: skip.unwrap_or(false),
             with,
+            detailed_with,
+            include_stack: include_stack.unwrap_or(false),
         }
     }
 }
 
+/// A set of attributes on top of the type `DataSize` is derived for.
+#[derive(Debug)]
+struct ContainerAttributes {
+    /// A constant heap size to use instead of deriving it (`data_size(const = ...)`).
+    pub const_size: Option<syn::Expr>,
+    /// A `STATIC_HEAP_SIZE` to use instead of the derived one (`data_size(static_heap_size = ...)`).
+    pub static_heap_size: Option<syn::Expr>,
+    /// An `IS_DYNAMIC` to use instead of the derived one (`data_size(is_dynamic = ...)`).
+    pub is_dynamic: Option<syn::Expr>,
+    /// Whether the type is an enum consisting of unit variants only (`data_size(unit_enum)`).
+    pub unit_enum: bool,
+    /// Whether to forward to the only non-skipped field (`data_size(transparent)`).
+    pub transparent: bool,
+    /// The type parameters to bound instead of the field types (`data_size(bound(...))`).
+    pub bound: Option<Vec<Ident>>,
+    /// The type parameters assumed to hold no heap data (`data_size(skip_bound(...))`).
+    pub skip_bound: Vec<Ident>,
+}
+
+impl ContainerAttributes {
+    /// Parses a set of attributes from untyped [`Attribute`]s.
+    fn parse(attrs: &[Attribute]) -> Self {
+        let mut const_size = None;
+        let mut static_heap_size = None;
+        let mut is_dynamic = None;
+        let mut unit_enum = None;
+        let mut transparent = None;
+        let mut bound = None;
+        let mut skip_bound = None;
+
+        for parsed in parse_data_attributes(attrs) {
+            match parsed {
+                DataAttribute::Const(expr) => {
+                    if const_size.is_some() {
+                        panic!("duplicated `const` attribute");
+                    } else {
+                        const_size = Some(expr);
+                    }
+                }
+                DataAttribute::StaticHeapSize(expr) => {
+                    if static_heap_size.is_some() {
+                        panic!("duplicated `static_heap_size` attribute");
+                    } else {
+                        static_heap_size = Some(expr);
+                    }
+                }
+                DataAttribute::IsDynamic(expr) => {
+                    if is_dynamic.is_some() {
+                        panic!("duplicated `is_dynamic` attribute");
+                    } else {
+                        is_dynamic = Some(expr);
+                    }
+                }
+                DataAttribute::UnitEnum => {
+                    if unit_enum.is_some() {
+                        panic!("duplicated `unit_enum` attribute");
+                    } else {
+                        unit_enum = Some(true);
+                    }
+                }
+                DataAttribute::Transparent => {
+                    if transparent.is_some() {
+                        panic!("duplicated `transparent` attribute");
+                    } else {
+                        transparent = Some(true);
+                    }
+                }
+                DataAttribute::Bound(params) => {
+                    if bound.is_some() {
+                        panic!("duplicated `bound` attribute");
+                    } else {
+                        bound = Some(params);
+                    }
+                }
+                DataAttribute::SkipBound(params) => {
+                    if skip_bound.is_some() {
+                        panic!("duplicated `skip_bound` attribute");
+                    } else {
+                        skip_bound = Some(params);
+                    }
+                }
+                other => panic!("`{}` is not supported on types", other.keyword()),
+            }
+        }
+
+        if const_size.is_some() && static_heap_size.is_some() {
+            panic!("`static_heap_size` cannot be combined with `const`");
+        }
+
+        if is_dynamic.is_some() && const_size.is_some() {
+            panic!("`is_dynamic` cannot be combined with `const`");
+        }
+
+        let unit_enum = unit_enum.unwrap_or(false);
+        if unit_enum && (const_size.is_some() || static_heap_size.is_some() || is_dynamic.is_some())
+        {
+            panic!(
+                "`unit_enum` cannot be combined with `const`, `static_heap_size` or `is_dynamic`"
+            );
+        }
+
+        let transparent = transparent.unwrap_or(false);
+        if transparent
+            && (const_size.is_some()
+                || static_heap_size.is_some()
+                || is_dynamic.is_some()
+                || unit_enum)
+        {
+            panic!(
+                "`transparent` cannot be combined with `const`, `static_heap_size`, `is_dynamic` \
+                 or `unit_enum`"
+            );
+        }
+
+        ContainerAttributes {
+            const_size,
+            static_heap_size,
+            is_dynamic,
+            unit_enum,
+            transparent,
+            bound,
+            skip_bound: skip_bound.unwrap_or_default(),
+        }
+    }
+
+    /// Returns `generics` without the type parameters listed in `data_size(skip_bound(...))`.
+    ///
+    /// Fields are only bounded if they contain any of the remaining parameters. Panics if any of
+    /// the listed parameters is not a type parameter of `generics` or also listed in `bound`.
+    fn bounded_generics(&self, generics: &Generics) -> Generics {
+        for param in &self.skip_bound {
+            if generics.type_params().all(|tp| tp.ident != *param) {
+                panic!(
+                    "`skip_bound` lists `{}`, which is not a type parameter",
+                    param
+                );
+            }
+
+            if self.bound.iter().flatten().any(|bound| bound == param) {
+                panic!("`{}` is listed in both `bound` and `skip_bound`", param);
+            }
+        }
+
+        let mut bounded = generics.clone();
+        bounded.params = generics
+            .params
+            .iter()
+            .filter(|param| match param {
+                syn::GenericParam::Type(TypeParam { ident, .. }) => {
+                    !self.skip_bound.contains(ident)
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        bounded
+    }
+
+    /// Returns whether `ty` is exactly one of the type parameters listed in
+    /// `data_size(skip_bound(...))`, in which case the field is skipped.
+    fn is_skipped_param(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Path(TypePath { qself: None, path }) => path
+                .get_ident()
+                .is_some_and(|ident| self.skip_bound.contains(ident)),
+            _ => false,
+        }
+    }
+
+    /// Returns the predicates for the type parameters listed in `data_size(bound(...))`.
+    ///
+    /// Panics if any of them is not a type parameter of `generics`.
+    fn bound_predicates(&self, generics: &Generics) -> Option<proc_macro2::TokenStream> {
+        let params = self.bound.as_ref()?;
+        let mut predicates = proc_macro2::TokenStream::new();
+
+        for param in params {
+            if generics.type_params().all(|tp| tp.ident != *param) {
+                panic!("`bound` lists `{}`, which is not a type parameter", param);
+            }
+
+            predicates.extend(quote!(#param : datasize::DataSize,));
+        }
+
+        Some(predicates)
+    }
+}
+
+/// Returns the attributes put on every generated impl.
+///
+/// The generated code is not always as tidy as handwritten code would be, e.g. `where` clauses may
+/// list the same bound once per field, so lints that might trigger on it are silenced to keep
+/// `#![deny(warnings)]` crates compiling.
+fn impl_attributes() -> proc_macro2::TokenStream {
+    quote!(
+        #[automatically_derived]
+        #[allow(
+            unused_variables,
+            unused_mut,
+            clippy::let_and_return,
+            clippy::type_repetition_in_bounds,
+            clippy::trait_duplication_in_bounds
+        )]
+    )
+}
+
+/// Derives `DataSize` with a constant heap size for any type.
+fn derive_const(name: Ident, generics: Generics, const_size: syn::Expr) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let attributes = impl_attributes();
+
+    TokenStream::from(quote! {
+        #attributes
+        impl #impl_generics datasize::DataSize for #name #ty_generics #where_clause {
+            const IS_DYNAMIC: bool = false;
+            const STATIC_HEAP_SIZE: usize = #const_size;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                Self::STATIC_HEAP_SIZE
+            }
+        }
+    })
+}
+
 /// Derives `DataSize` for a `struct`
-fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenStream {
+fn derive_for_struct(
+    name: Ident,
+    generics: Generics,
+    container_attrs: &ContainerAttributes,
+    ds: DataStruct,
+) -> TokenStream {
     let fields = ds.fields;
+    let bound_predicates = container_attrs.bound_predicates(&generics);
+    let bounded_generics = container_attrs.bounded_generics(&generics);
 
     let mut where_clauses = proc_macro2::TokenStream::new();
     let mut is_dynamic = proc_macro2::TokenStream::new();
-    let mut static_heap_size = proc_macro2::TokenStream::new();
+    // Summed up with saturating arithmetic, so pathological types saturate at `usize::MAX` instead
+    // of failing to compile.
+    let mut static_heap_size = quote!(0usize);
     let mut dynamic_size = proc_macro2::TokenStream::new();
+    let mut limited_size = proc_macro2::TokenStream::new();
     let mut detail_calls = proc_macro2::TokenStream::new();
+    let mut visit_calls = proc_macro2::TokenStream::new();
+
+    let mut field_names = Vec::new();
 
     let mut has_manual_field = false;
 
     for (idx, field) in fields.iter().enumerate() {
         let field_attrs = DataSizeAttributes::parse(&field.attrs);
-        if field_attrs.skip {
+        if field_attrs.skip || container_attrs.is_skipped_param(&field.ty) {
             continue;
         }
 
@@ -273,7 +701,10 @@ fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenSt
         // We need a where clause for every non-skipped, non-with field. We try our best to filter
         // out bounds here that are not needed (e.g. `u8: DataSize`), as they can be problematic
         // when mixing `pub(super)` and `pub` visiblity restrictions.
-        if field_attrs.with.is_none() && contains_generic(&generics, ty) {
+        if bound_predicates.is_none()
+            && field_attrs.with.is_none()
+            && contains_generic(&bounded_generics, ty)
+        {
             if where_clauses.is_empty() {
                 where_clauses.extend(quote!(where));
             }
@@ -287,23 +718,20 @@ fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenSt
             is_dynamic.extend(quote!(|));
         }
 
-        if !static_heap_size.is_empty() {
-            static_heap_size.extend(quote!(+));
-        }
-
         if !dynamic_size.is_empty() {
             dynamic_size.extend(quote!(+));
+            limited_size.extend(quote!(+));
         }
 
         is_dynamic.extend(quote!(<#ty as datasize::DataSize>));
         is_dynamic.extend(quote!(::IS_DYNAMIC));
 
+        // Fields sized through `with` have no static heap size.
         if field_attrs.with.is_none() {
-            static_heap_size.extend(quote!(<#ty as datasize::DataSize>));
-            static_heap_size.extend(quote!(::STATIC_HEAP_SIZE));
-        } else {
-            static_heap_size.extend(quote!(0));
-        };
+            static_heap_size.extend(quote!(
+                .saturating_add(<#ty as datasize::DataSize>::STATIC_HEAP_SIZE)
+            ));
+        }
 
         let handle = if let Some(ref ident) = &field.ident {
             quote!(#ident)
@@ -312,11 +740,20 @@ fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenSt
             quote!(#idx)
         };
 
+        // Tuple struct fields are keyed by their position in detailed estimates.
         let name = if let Some(ref ident) = &field.ident {
             ident.to_string()
         } else {
-            "idx".to_string()
+            idx.to_string()
         };
+        field_names.push(name.clone());
+
+        // A manual detailed estimate takes precedence over the one derived from the flat estimate.
+        if let Some(ref manual) = field_attrs.detailed_with {
+            detail_calls.extend(quote!(
+                members.insert(#name, #manual(&self.#handle));
+            ));
+        }
 
         match field_attrs.with {
             Some(manual) => {
@@ -324,8 +761,18 @@ fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenSt
                     #manual(&self.#handle)
                 ));
 
-                detail_calls.extend(quote!(
-                    members.insert(#name, datasize::MemUsageNode::Size(#manual(&self.#handle)));
+                limited_size.extend(quote!(
+                    #manual(&self.#handle)
+                ));
+
+                if field_attrs.detailed_with.is_none() {
+                    detail_calls.extend(quote!(
+                        members.insert(#name, datasize::MemUsageNode::Size(#manual(&self.#handle)));
+                    ));
+                }
+
+                visit_calls.extend(quote!(
+                    datasize::visit_opaque(#manual(&self.#handle), visitor);
                 ));
             }
             None => {
@@ -333,22 +780,55 @@ fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenSt
                     datasize::data_size::<#ty>(&self.#handle)
                 ));
 
-                detail_calls.extend(quote!(
-                    members.insert(#name, self.#handle.estimate_detailed_heap_size());
+                limited_size.extend(quote!(
+                    datasize::DataSize::estimate_heap_size_with_limit(&self.#handle, limit)
+                ));
+
+                if field_attrs.detailed_with.is_none() {
+                    detail_calls.extend(quote!(
+                        members.insert(#name, self.#handle.estimate_detailed_heap_size());
+                    ));
+                }
+
+                visit_calls.extend(quote!(
+                    datasize::DataSize::visit(&self.#handle, visitor);
                 ));
             }
         }
+
+        // The stack size is constant, so it is part of the static heap size as well.
+        if field_attrs.include_stack {
+            let stack_size = quote!(::core::mem::size_of::<#ty>());
+
+            static_heap_size.extend(quote!(.saturating_add(#stack_size)));
+            dynamic_size.extend(quote!(+ #stack_size));
+            limited_size.extend(quote!(+ #stack_size));
+            detail_calls.extend(with_stack_detail(&name, &stack_size));
+            visit_calls.extend(quote!(
+                datasize::visit_opaque(#stack_size, visitor);
+            ));
+        }
     }
 
     // Handle structs with no fields.
     if is_dynamic.is_empty() {
         is_dynamic.extend(quote!(false));
     }
-    if static_heap_size.is_empty() {
-        static_heap_size.extend(quote!(0));
+
+    // A manually specified `STATIC_HEAP_SIZE` replaces the derived one.
+    if let Some(ref manual) = container_attrs.static_heap_size {
+        static_heap_size = quote!(#manual);
     }
     if dynamic_size.is_empty() {
         dynamic_size.extend(quote!(0));
+        limited_size.extend(quote!(0));
+    }
+
+    // Explicitly listed bounds replace the ones derived from the fields.
+    if let Some(predicates) = bound_predicates {
+        if !predicates.is_empty() {
+            where_clauses.extend(quote!(where #predicates));
+        }
     }
 
     // Ensure that any `where` clause on the struct itself is preserved, otherwise the impl is
@@ -359,6 +839,9 @@ fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenSt
 
     let detailed_impl = if cfg!(feature = "detailed") {
         quote!(
+            const DATA_SIZE_FIELDS: &'static [&'static str] = &[#(#field_names),*];
+
+            #[inline]
             fn estimate_detailed_heap_size(&self) -> datasize::MemUsageNode {
                 let mut members = ::std::collections::HashMap::new();
                 #detail_calls
@@ -369,30 +852,191 @@ fn derive_for_struct(name: Ident, generics: Generics, ds: DataStruct) -> TokenSt
         quote!()
     };
 
+    let visit_impl = if cfg!(feature = "visitor") {
+        quote!(
+            #[inline]
+            fn visit<__V: datasize::DataSizeVisitor>(&self, visitor: &mut __V) {
+                #visit_calls
+            }
+        )
+    } else {
+        quote!()
+    };
+
     // If we found at least one manual field, ensure we recalculate heap size always.
     if has_manual_field {
         is_dynamic = proc_macro2::TokenStream::new();
         is_dynamic.extend(quote!(true));
     }
 
+    // A manually specified `IS_DYNAMIC` replaces the derived one, even with manual fields.
+    if let Some(ref manual) = container_attrs.is_dynamic {
+        is_dynamic = quote!(#manual);
+    }
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let attributes = impl_attributes();
+
     TokenStream::from(quote! {
-        impl #generics datasize::DataSize for #name #generics #where_clauses {
+        #attributes
+        impl #impl_generics datasize::DataSize for #name #ty_generics #where_clauses {
             const IS_DYNAMIC: bool = #is_dynamic;
             const STATIC_HEAP_SIZE: usize = #static_heap_size;
 
+            #[inline]
             fn estimate_heap_size(&self) -> usize {
                 #dynamic_size
             }
 
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut datasize::Limit) -> usize {
+                #limited_size
+            }
+
+            #detailed_impl
+
+            #visit_impl
+        }
+    })
+}
+
+/// Derives `DataSize` for a struct by forwarding to its only non-skipped field.
+fn derive_transparent(
+    name: Ident,
+    generics: Generics,
+    container_attrs: &ContainerAttributes,
+    ds: DataStruct,
+) -> TokenStream {
+    let mut fields = ds.fields.iter().enumerate().filter(|(_, field)| {
+        !DataSizeAttributes::parse(&field.attrs).skip
+            && !container_attrs.is_skipped_param(&field.ty)
+    });
+
+    let (idx, field) = match (fields.next(), fields.count()) {
+        (Some(field), 0) => field,
+        (None, _) => panic!("`transparent` requires exactly one non-skipped field, but found none"),
+        (Some(_), others) => panic!(
+            "`transparent` requires exactly one non-skipped field, but found {}",
+            others + 1
+        ),
+    };
+
+    let field_attrs = DataSizeAttributes::parse(&field.attrs);
+    if field_attrs.with.is_some()
+        || field_attrs.detailed_with.is_some()
+        || field_attrs.include_stack
+    {
+        panic!(
+            "the field of a `transparent` struct cannot use `with`, `detailed_with` or \
+             `include_stack`"
+        );
+    }
+
+    let ty = &field.ty;
+    let handle = if let Some(ref ident) = &field.ident {
+        quote!(#ident)
+    } else {
+        let idx = Index::from(idx);
+        quote!(#idx)
+    };
+
+    // Explicitly listed bounds replace the one derived from the field.
+    let mut where_clause = proc_macro2::TokenStream::new();
+    match container_attrs.bound_predicates(&generics) {
+        Some(predicates) => {
+            if !predicates.is_empty() {
+                where_clause.extend(quote!(where #predicates));
+            }
+        }
+        None => {
+            if contains_generic(&container_attrs.bounded_generics(&generics), ty) {
+                where_clause.extend(quote!(where #ty : datasize::DataSize,));
+            }
+        }
+    }
+
+    if let Some(WhereClause { ref predicates, .. }) = generics.where_clause {
+        if where_clause.is_empty() {
+            where_clause.extend(quote!(where));
+        }
+        where_clause.extend(quote!(#predicates));
+    }
+
+    let detailed_impl = if cfg!(feature = "detailed") {
+        quote!(
+            const DATA_SIZE_FIELDS: &'static [&'static str] =
+                <#ty as datasize::DataSize>::DATA_SIZE_FIELDS;
+
+            #[inline]
+            fn estimate_detailed_heap_size(&self) -> datasize::MemUsageNode {
+                datasize::DataSize::estimate_detailed_heap_size(&self.#handle)
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let visit_impl = if cfg!(feature = "visitor") {
+        quote!(
+            #[inline]
+            fn visit<__V: datasize::DataSizeVisitor>(&self, visitor: &mut __V) {
+                datasize::DataSize::visit(&self.#handle, visitor)
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let attributes = impl_attributes();
+
+    TokenStream::from(quote! {
+        #attributes
+        impl #impl_generics datasize::DataSize for #name #ty_generics #where_clause {
+            const IS_DYNAMIC: bool = <#ty as datasize::DataSize>::IS_DYNAMIC;
+            const STATIC_HEAP_SIZE: usize = <#ty as datasize::DataSize>::STATIC_HEAP_SIZE;
+
+            #[inline]
+            fn estimate_heap_size(&self) -> usize {
+                datasize::DataSize::estimate_heap_size(&self.#handle)
+            }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut datasize::Limit) -> usize {
+                datasize::DataSize::estimate_heap_size_with_limit(&self.#handle, limit)
+            }
+
             #detailed_impl
+
+            #visit_impl
         }
     })
 }
 
+/// Adds `stack_size` to the detailed estimate of the field inserted under `key`.
+fn with_stack_detail(key: &str, stack_size: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote!(
+        if let Some(node) = members.remove(#key) {
+            members.insert(#key, datasize::__detailed_with_stack(node, #stack_size));
+        }
+    )
+}
+
 /// Derives `DataSize` for an `enum`
-fn derive_for_enum(name: Ident, generics: Generics, de: DataEnum) -> TokenStream {
+fn derive_for_enum(
+    name: Ident,
+    generics: Generics,
+    container_attrs: &ContainerAttributes,
+    de: DataEnum,
+) -> TokenStream {
     let mut match_arms = proc_macro2::TokenStream::new();
+    let mut limited_match_arms = proc_macro2::TokenStream::new();
+    let mut visit_match_arms = proc_macro2::TokenStream::new();
+    let mut detail_match_arms = proc_macro2::TokenStream::new();
     let mut where_types = proc_macro2::TokenStream::new();
+    let bounded_generics = container_attrs.bounded_generics(&generics);
+
+    let mut variant_keys = Vec::new();
 
     let mut skipped = false;
     for variant in de.variants.into_iter() {
@@ -404,9 +1048,16 @@ fn derive_for_enum(name: Ident, generics: Generics, de: DataEnum) -> TokenStream
         }
 
         let variant_ident = variant.ident;
+        // Detailed estimates are keyed by the qualified name of the active variant.
+        let variant_key = format!("{}::{}", name, variant_ident);
+        let is_unit = matches!(variant.fields, syn::Fields::Unit);
+        variant_keys.push(variant_key.clone());
 
         let mut field_match = proc_macro2::TokenStream::new();
         let mut field_calc = proc_macro2::TokenStream::new();
+        let mut field_calc_limited = proc_macro2::TokenStream::new();
+        let mut field_visit = proc_macro2::TokenStream::new();
+        let mut field_details = proc_macro2::TokenStream::new();
 
         match variant.fields {
             syn::Fields::Named(fields) => {
@@ -415,23 +1066,47 @@ fn derive_for_enum(name: Ident, generics: Generics, de: DataEnum) -> TokenStream
                 for field in fields.named.into_iter() {
                     let ident = field.ident.expect("named fields must have idents");
                     let ds_attrs = DataSizeAttributes::parse(&field.attrs);
+                    let skip = ds_attrs.skip || container_attrs.is_skipped_param(&field.ty);
 
-                    if ds_attrs.skip {
-                        left.extend(quote!(#ident:_));
+                    if skip {
+                        left.extend(quote!(#ident: _,));
                     } else {
                         left.extend(quote!(#ident ,));
 
                         let ty = field.ty;
-                        if contains_generic(&generics, &ty) {
+                        if contains_generic(&bounded_generics, &ty) {
                             where_types.extend(quote!(#ty : datasize::DataSize,));
                         }
                     }
 
-                    if !ds_attrs.skip {
+                    if !skip {
                         if !field_calc.is_empty() {
                             field_calc.extend(quote!(+));
+                            field_calc_limited.extend(quote!(+));
                         }
                         field_calc.extend(quote!(DataSize::estimate_heap_size(#ident)));
+                        field_calc_limited.extend(quote!(
+                            DataSize::estimate_heap_size_with_limit(#ident, limit)
+                        ));
+                        field_visit.extend(quote!(DataSize::visit(#ident, visitor);));
+
+                        let key = ident.to_string();
+                        field_details.extend(match ds_attrs.detailed_with {
+                            Some(manual) => quote!(members.insert(#key, #manual(#ident));),
+                            None => quote!(
+                                members.insert(#key, DataSize::estimate_detailed_heap_size(#ident));
+                            ),
+                        });
+
+                        if ds_attrs.include_stack {
+                            let stack_size = quote!(::core::mem::size_of_val(#ident));
+
+                            field_calc.extend(quote!(+ #stack_size));
+                            field_calc_limited.extend(quote!(+ #stack_size));
+                            field_visit
+                                .extend(quote!(datasize::visit_opaque(#stack_size, visitor);));
+                            field_details.extend(with_stack_detail(&key, &stack_size));
+                        }
                     }
                 }
 
@@ -444,22 +1119,49 @@ fn derive_for_enum(name: Ident, generics: Generics, de: DataEnum) -> TokenStream
 
                 for (idx, field) in fields.unnamed.into_iter().enumerate() {
                     let field_ds_attrs = DataSizeAttributes::parse(&field.attrs);
+                    let skip = field_ds_attrs.skip || container_attrs.is_skipped_param(&field.ty);
 
                     let ident = Ident::new(
-                        &format!("{}f{}", if field_ds_attrs.skip { "_" } else { "" }, idx),
+                        &format!("{}f{}", if skip { "_" } else { "" }, idx),
                         proc_macro2::Span::call_site(),
                     );
 
                     left.extend(quote!(#ident ,));
 
-                    if !field_ds_attrs.skip {
+                    if !skip {
                         if !field_calc.is_empty() {
                             field_calc.extend(quote!(+));
+                            field_calc_limited.extend(quote!(+));
                         }
                         field_calc.extend(quote!(DataSize::estimate_heap_size(#ident)));
+                        field_calc_limited.extend(quote!(
+                            DataSize::estimate_heap_size_with_limit(#ident, limit)
+                        ));
+                        field_visit.extend(quote!(DataSize::visit(#ident, visitor);));
+
+                        // Tuple variant fields are keyed by their position in detailed estimates.
+                        let key = idx.to_string();
+                        field_details.extend(match field_ds_attrs.detailed_with {
+                            Some(manual) => quote!(members.insert(#key, #manual(#ident));),
+                            None => quote!(
+                                members.insert(#key, DataSize::estimate_detailed_heap_size(#ident));
+                            ),
+                        });
+
+                        if field_ds_attrs.include_stack {
+                            let stack_size = quote!(::core::mem::size_of_val(#ident));
+
+                            field_calc.extend(quote!(+ #stack_size));
+                            field_calc_limited.extend(quote!(+ #stack_size));
+                            field_visit
+                                .extend(quote!(datasize::visit_opaque(#stack_size, visitor);));
+                            field_details.extend(with_stack_detail(&key, &stack_size));
+                        }
 
                         let ty = field.ty;
-                        where_types.extend(quote!(#ty : datasize::DataSize,));
+                        if contains_generic(&bounded_generics, &ty) {
+                            where_types.extend(quote!(#ty : datasize::DataSize,));
+                        }
                     }
                 }
 
@@ -469,23 +1171,60 @@ fn derive_for_enum(name: Ident, generics: Generics, de: DataEnum) -> TokenStream
             }
             syn::Fields::Unit => {
                 field_calc.extend(quote!(0));
+                field_calc_limited.extend(quote!(0));
             }
         }
 
         if field_calc.is_empty() {
             field_calc.extend(quote!(0));
+            field_calc_limited.extend(quote!(0));
         }
 
         match_arms.extend(quote!(
             #name::#variant_ident #field_match => { #field_calc }
         ));
+        limited_match_arms.extend(quote!(
+            #name::#variant_ident #field_match => { #field_calc_limited }
+        ));
+        visit_match_arms.extend(quote!(
+            #name::#variant_ident #field_match => { #field_visit }
+        ));
+        detail_match_arms.extend(if is_unit {
+            quote!(
+                #name::#variant_ident #field_match => {
+                    variants.insert(#variant_key, datasize::MemUsageNode::Size(0));
+                }
+            )
+        } else {
+            quote!(
+                #name::#variant_ident #field_match => {
+                    let mut members = ::std::collections::HashMap::new();
+                    #field_details
+                    variants.insert(#variant_key, datasize::MemUsageNode::Detailed(members));
+                }
+            )
+        });
     }
 
     // If we skipped any variant, add a fallback.
     if skipped {
         match_arms.extend(quote! {
             _ => 0,
-        })
+        });
+        limited_match_arms.extend(quote! {
+            _ => 0,
+        });
+        visit_match_arms.extend(quote! {
+            _ => {}
+        });
+        detail_match_arms.extend(quote! {
+            _ => {}
+        });
+    }
+
+    // Explicitly listed bounds replace the ones derived from the fields.
+    if let Some(predicates) = container_attrs.bound_predicates(&generics) {
+        where_types = predicates;
     }
 
     let mut where_clause = proc_macro2::TokenStream::new();
@@ -503,21 +1242,67 @@ fn derive_for_enum(name: Ident, generics: Generics, de: DataEnum) -> TokenStream
     //       variant (which are the sum of their fields). `min` can be determined by the
     //       `datasize::min` function, which is a `const fn` variant of `min`.
     let mut is_dynamic = true;
-    let static_heap_size = 0usize;
+    let static_heap_size = match container_attrs.static_heap_size {
+        Some(ref manual) => quote!(#manual),
+        None => quote!(0),
+    };
 
     // Handle enums with no fields.
     if match_arms.is_empty() {
         match_arms.extend(quote!(_ => 0));
+        limited_match_arms.extend(quote!(_ => 0));
+        visit_match_arms.extend(quote!(_ => {}));
+        detail_match_arms.extend(quote!(_ => {}));
         is_dynamic = false;
     }
 
+    // A manually specified `IS_DYNAMIC` replaces the derived one.
+    let is_dynamic = match container_attrs.is_dynamic {
+        Some(ref manual) => quote!(#manual),
+        None => quote!(#is_dynamic),
+    };
+
     // Ensure that any `where` clause on the struct enum is preserved.
     if let Some(WhereClause { ref predicates, .. }) = generics.where_clause {
         where_clause.extend(quote!(#predicates));
     }
 
+    let visit_impl = if cfg!(feature = "visitor") {
+        quote!(
+            #[inline]
+            fn visit<__V: datasize::DataSizeVisitor>(&self, visitor: &mut __V) {
+                match self {
+                    #visit_match_arms
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let detailed_impl = if cfg!(feature = "detailed") {
+        quote!(
+            const DATA_SIZE_FIELDS: &'static [&'static str] = &[#(#variant_keys),*];
+
+            #[inline]
+            fn estimate_detailed_heap_size(&self) -> datasize::MemUsageNode {
+                let mut variants = ::std::collections::HashMap::new();
+                match self {
+                    #detail_match_arms
+                }
+                datasize::MemUsageNode::Detailed(variants)
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let attributes = impl_attributes();
+
     TokenStream::from(quote! {
-        impl #generics DataSize for #name #generics #where_clause {
+        #attributes
+        impl #impl_generics DataSize for #name #ty_generics #where_clause {
 
             const IS_DYNAMIC: bool = #is_dynamic;
             const STATIC_HEAP_SIZE: usize = #static_heap_size;
@@ -528,6 +1313,17 @@ fn derive_for_enum(name: Ident, generics: Generics, de: DataEnum) -> TokenStream
                     #match_arms
                 }
             }
+
+            #[inline]
+            fn estimate_heap_size_with_limit(&self, limit: &mut datasize::Limit) -> usize {
+                match self {
+                    #limited_match_arms
+                }
+            }
+
+            #detailed_impl
+
+            #visit_impl
         }
     })
 }